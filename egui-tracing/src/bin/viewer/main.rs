@@ -0,0 +1,214 @@
+//! Shows log files in the [`Logs`] widget. Reads JSON Lines written by
+//! `egui_tracing` or `tracing_subscriber::fmt().json()`, and the default text
+//! format of `tracing_subscriber::fmt()`.
+
+mod tail;
+mod text;
+
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::Ordering;
+
+use egui_tracing::{EventCollector, Logs};
+
+use self::tail::Tail;
+
+const USAGE: &str = "\
+Usage: egui-tracing-viewer [OPTIONS] [FILE]...
+
+Options:
+      --no-follow        Don't show lines appended to the files
+      --max-events <N>   Keep at most N events per file
+  -h, --help             Print help";
+
+struct Args {
+    follow: bool,
+    max_events: Option<usize>,
+    paths: Vec<PathBuf>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        follow: true,
+        max_events: None,
+        paths: Vec::new(),
+    };
+    let mut iter = std::env::args_os().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.to_str() {
+            Some("-h" | "--help") => {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            Some("--no-follow") => args.follow = false,
+            Some("--max-events") => {
+                let value = iter.next().ok_or("--max-events needs a value")?;
+                let value = value.to_string_lossy();
+                args.max_events = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid value for --max-events: {}", value))?,
+                );
+            }
+            Some(option) if option.starts_with('-') && option != "-" => {
+                return Err(format!("unknown option {}", option));
+            }
+            _ => args.paths.push(arg.into()),
+        }
+    }
+    Ok(args)
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|err| {
+        eprintln!("error: {}\n\n{}", err, USAGE);
+        process::exit(2);
+    });
+
+    let options = eframe::NativeOptions {
+        viewport: egui::ViewportBuilder::default()
+            .with_inner_size(egui::vec2(1000.0, 600.0))
+            .with_drag_and_drop(true),
+        ..Default::default()
+    };
+    eframe::run_native(
+        "egui-tracing viewer",
+        options,
+        Box::new(|cc| Box::new(Viewer::new(cc.egui_ctx.clone(), args))),
+    )
+    .unwrap();
+}
+
+struct LogFile {
+    path: PathBuf,
+    collector: EventCollector,
+    tail: Tail,
+}
+
+struct Viewer {
+    ctx: egui::Context,
+    follow: bool,
+    max_events: Option<usize>,
+    files: Vec<LogFile>,
+    selected: usize,
+}
+
+impl Viewer {
+    fn new(ctx: egui::Context, args: Args) -> Self {
+        let mut viewer = Self {
+            ctx,
+            follow: args.follow,
+            max_events: args.max_events,
+            files: Vec::new(),
+            selected: 0,
+        };
+        for path in args.paths {
+            viewer.open(path);
+        }
+        viewer
+    }
+
+    fn open(&mut self, path: PathBuf) {
+        if let Some(index) = self.files.iter().position(|file| file.path == path) {
+            self.selected = index;
+            return;
+        }
+
+        let mut collector = EventCollector::new();
+        if let Some(max_events) = self.max_events {
+            collector = collector.with_max_events(max_events);
+        }
+        collector.set_egui_context(self.ctx.clone());
+        let tail = Tail::spawn(path.clone(), collector.clone(), self.follow);
+        self.files.push(LogFile {
+            path,
+            collector,
+            tail,
+        });
+        self.selected = self.files.len() - 1;
+    }
+}
+
+impl eframe::App for Viewer {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let dropped: Vec<_> = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|file| file.path.clone())
+                .collect()
+        });
+        for path in dropped {
+            self.open(path);
+        }
+
+        egui::TopBottomPanel::top("files").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                if ui.button("Open…").clicked() {
+                    if let Some(paths) = rfd::FileDialog::new()
+                        .add_filter("Logs", &["log", "jsonl", "json", "txt"])
+                        .add_filter("All files", &["*"])
+                        .pick_files()
+                    {
+                        for path in paths {
+                            self.open(path);
+                        }
+                    }
+                }
+                ui.checkbox(&mut self.follow, "Follow")
+                    .on_hover_text("Show lines appended to files opened from now on");
+                ui.separator();
+
+                let mut closed = None;
+                for (index, file) in self.files.iter().enumerate() {
+                    let name = file.path.file_name().map_or_else(
+                        || file.path.to_string_lossy(),
+                        |name| name.to_string_lossy(),
+                    );
+                    if ui
+                        .selectable_label(self.selected == index, name)
+                        .on_hover_text(file.path.to_string_lossy())
+                        .clicked()
+                    {
+                        self.selected = index;
+                    }
+                    if ui.small_button("×").on_hover_text("Close").clicked() {
+                        closed = Some(index);
+                    }
+                }
+                if let Some(index) = closed {
+                    self.files.remove(index);
+                    self.selected = self.selected.min(self.files.len().saturating_sub(1));
+                }
+            });
+        });
+
+        let Some(file) = self.files.get(self.selected) else {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui.centered_and_justified(|ui| ui.label("Open a log file or drop one here"));
+            });
+            return;
+        };
+
+        egui::TopBottomPanel::bottom("status").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(file.path.to_string_lossy());
+                let skipped = file.tail.status.skipped.load(Ordering::Relaxed);
+                if skipped > 0 {
+                    ui.separator();
+                    ui.label(format!("{} lines skipped", skipped))
+                        .on_hover_text("Lines that couldn't be parsed as an event");
+                }
+                if let Some(error) = &*file.tail.status.error.lock().unwrap() {
+                    ui.separator();
+                    ui.colored_label(ui.visuals().error_fg_color, error);
+                }
+            });
+        });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            Logs::new(file.collector.clone())
+                .id_source(&file.path)
+                .show(ui);
+        });
+    }
+}
@@ -0,0 +1,182 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use egui_tracing::tracing::{json, CollectedEvent};
+use egui_tracing::EventCollector;
+
+use crate::text;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Default)]
+pub struct TailStatus {
+    /// Lines that couldn't be parsed as an event.
+    pub skipped: AtomicUsize,
+    pub error: Mutex<Option<String>>,
+}
+
+/// Reads a log file into a collector on a background thread, following it
+/// as it grows if `follow` is set. Stops when dropped.
+pub struct Tail {
+    stop: Arc<AtomicBool>,
+    pub status: Arc<TailStatus>,
+}
+
+impl Tail {
+    pub fn spawn(path: PathBuf, collector: EventCollector, follow: bool) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let status = Arc::new(TailStatus::default());
+        let tail = Self {
+            stop: stop.clone(),
+            status: status.clone(),
+        };
+        thread::spawn(move || {
+            if let Err(err) = run(&path, &collector, follow, &stop, &status) {
+                *status.error.lock().unwrap() = Some(err.to_string());
+            }
+        });
+        tail
+    }
+}
+
+impl Drop for Tail {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn run(
+    path: &Path,
+    collector: &EventCollector,
+    follow: bool,
+    stop: &AtomicBool,
+    status: &TailStatus,
+) -> io::Result<()> {
+    let file = File::open(path)?;
+    let mut id = file_id(&file.metadata()?);
+    let mut reader = BufReader::new(file);
+    let mut parser = Parser::default();
+    let mut line = Vec::new();
+    let mut pos = 0;
+    // Whether anything was read since the last poll.
+    let mut fresh = false;
+    while !stop.load(Ordering::Relaxed) {
+        let read = reader.read_until(b'\n', &mut line)?;
+        pos += read as u64;
+        fresh |= read > 0;
+        if line.ends_with(b"\n") {
+            parser.push(&String::from_utf8_lossy(&line), collector);
+            line.clear();
+            continue;
+        }
+
+        // At the end of the file, possibly in the middle of a line that is
+        // still being written. The last text event may still be continued by
+        // the next lines, so it's only added once the input really ends, or
+        // when following, once a poll found nothing new.
+        if !follow {
+            parser.push(&String::from_utf8_lossy(&line), collector);
+            parser.flush(collector);
+            break;
+        }
+        if !fresh && line.is_empty() {
+            parser.flush(collector);
+        }
+        fresh = false;
+        status.skipped.store(parser.skipped, Ordering::Relaxed);
+        thread::sleep(POLL_INTERVAL);
+
+        let meta = match fs::metadata(path) {
+            Ok(meta) => meta,
+            // Rotated, but not created again yet.
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        // Start over if the file was replaced or truncated, e.g. when it's
+        // rotated. Lines may have been written to a replaced file since it
+        // was last read, so it's read to the end first.
+        let replaced = file_id(&meta) != id;
+        if replaced || meta.len() < pos {
+            if replaced {
+                while reader.read_until(b'\n', &mut line)? > 0 {
+                    if line.ends_with(b"\n") {
+                        parser.push(&String::from_utf8_lossy(&line), collector);
+                        line.clear();
+                    }
+                }
+                parser.push(&String::from_utf8_lossy(&line), collector);
+            }
+            parser.flush(collector);
+            let file = File::open(path)?;
+            id = file_id(&file.metadata()?);
+            reader = BufReader::new(file);
+            line.clear();
+            pos = 0;
+        }
+    }
+    status.skipped.store(parser.skipped, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Identifies the file behind a path, to notice when it's replaced. Only
+/// truncation is noticed where this isn't available.
+#[cfg(unix)]
+fn file_id(meta: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some((meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_meta: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Detects the format of each line, so JSON and text logs can be mixed.
+#[derive(Default)]
+struct Parser {
+    /// The last text event, which following lines may continue.
+    pending: Option<CollectedEvent>,
+    skipped: usize,
+}
+
+impl Parser {
+    fn push(&mut self, line: &str, collector: &EventCollector) {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            return;
+        }
+
+        if line.trim_start().starts_with('{') {
+            self.flush(collector);
+            match json::parse_event(line) {
+                Ok(event) => collector.import(event),
+                Err(_) => self.skipped += 1,
+            }
+            return;
+        }
+
+        let line = text::strip_ansi(line);
+        match text::parse_line(&line) {
+            Some(event) => {
+                self.flush(collector);
+                self.pending = Some(event);
+            }
+            None => match &mut self.pending {
+                Some(event) => text::append_line(event, &line),
+                None => self.skipped += 1,
+            },
+        }
+    }
+
+    fn flush(&mut self, collector: &EventCollector) {
+        if let Some(event) = self.pending.take() {
+            collector.import(event);
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Parsing of the default text format of `tracing_subscriber::fmt`:
+//!
+//! ```text
+//! 2024-05-01T12:00:00.123456Z  INFO request{path="/"}: my_app: hello id=42
+//! ```
+//!
+//! The format isn't meant to be parsed, so this is best effort. ANSI colors
+//! are stripped, and lines that don't start with a level are continuations of
+//! the previous event's message.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+use egui_tracing::tracing::{CollectedEvent, FieldValue, SpanContext, ThreadInfo};
+use regex::Regex;
+use tracing::Level;
+
+fn ansi() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"\x1b\[[0-9;]*m").unwrap())
+}
+
+fn span_list() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^((?:[\w-]+(?:\{[^}]*\})?:)+) ").unwrap())
+}
+
+fn span() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"([\w-]+)(?:\{([^}]*)\})?:").unwrap())
+}
+
+fn target() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^([\w.-]+(?:::[\w.-]+)*): ").unwrap())
+}
+
+fn field() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(?:^| )([A-Za-z_][\w.]*)=").unwrap())
+}
+
+pub fn strip_ansi(line: &str) -> String {
+    ansi().replace_all(line, "").into_owned()
+}
+
+/// Parses a line without ANSI colors.
+pub fn parse_line(line: &str) -> Option<CollectedEvent> {
+    let (first, rest) = split_token(line)?;
+    let (time, level, rest) = match DateTime::parse_from_rfc3339(first) {
+        Ok(time) => {
+            let (level, rest) = split_token(rest)?;
+            (time.with_timezone(&Local), level, rest)
+        }
+        // Without a timer the line starts with the level.
+        Err(_) => (Local::now(), first, rest),
+    };
+    let level = parse_level(level)?;
+
+    let mut rest = rest;
+    let mut spans = Vec::new();
+    // A span list is always followed by the target, which on its own looks
+    // like a span without fields.
+    if let Some(captures) = span_list().captures(rest) {
+        let after = &rest[captures[0].len()..];
+        if target().is_match(after) {
+            spans = span()
+                .captures_iter(&captures[1])
+                .map(|span| SpanContext {
+                    id: 0,
+                    name: span[1].to_owned(),
+                    target: String::new(),
+                    level,
+                    fields: span
+                        .get(2)
+                        .map_or_else(BTreeMap::new, |fields| parse_fields(fields.as_str()).1),
+                })
+                .collect();
+            rest = after;
+        }
+    }
+    let mut event_target = String::new();
+    if let Some(captures) = target().captures(rest) {
+        event_target = captures[1].to_owned();
+        rest = &rest[captures[0].len()..];
+    }
+
+    let (message, mut fields) = parse_fields(rest);
+    if let Some(message) = message {
+        fields.insert("message".to_owned(), FieldValue::Str(message));
+    }
+    Some(CollectedEvent {
+        target: event_target,
+        level,
+        fields,
+        time,
+        spans,
+        file: None,
+        line: None,
+        module_path: None,
+        thread: ThreadInfo {
+            name: None,
+            id: String::new(),
+        },
+    })
+}
+
+/// Appends a continuation line to the message of `event`.
+pub fn append_line(event: &mut CollectedEvent, line: &str) {
+    match event.fields.get_mut("message") {
+        Some(FieldValue::Str(message)) => {
+            message.push('\n');
+            message.push_str(line);
+        }
+        _ => {
+            event
+                .fields
+                .insert("message".to_owned(), FieldValue::Str(line.to_owned()));
+        }
+    }
+}
+
+fn split_token(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start();
+    if s.is_empty() {
+        return None;
+    }
+    Some(s.split_once(' ').unwrap_or((s, "")))
+}
+
+fn parse_level(s: &str) -> Option<Level> {
+    match s {
+        "TRACE" => Some(Level::TRACE),
+        "DEBUG" => Some(Level::DEBUG),
+        "INFO" => Some(Level::INFO),
+        "WARN" => Some(Level::WARN),
+        "ERROR" => Some(Level::ERROR),
+        _ => None,
+    }
+}
+
+/// Splits `message key=value ...` into the message and fields.
+fn parse_fields(s: &str) -> (Option<String>, BTreeMap<String, FieldValue>) {
+    let keys: Vec<_> = field().captures_iter(s).collect();
+    let message_end = keys
+        .first()
+        .map_or(s.len(), |key| key.get(0).unwrap().start());
+    let message = Some(s[..message_end].trim())
+        .filter(|message| !message.is_empty())
+        .map(str::to_owned);
+
+    let mut fields = BTreeMap::new();
+    for (i, key) in keys.iter().enumerate() {
+        let start = key.get(0).unwrap().end();
+        let end = keys
+            .get(i + 1)
+            .map_or(s.len(), |next| next.get(0).unwrap().start());
+        fields.insert(key[1].to_owned(), parse_value(&s[start..end]));
+    }
+    (message, fields)
+}
+
+fn parse_value(s: &str) -> FieldValue {
+    if let Some(s) = s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        FieldValue::Str(s.replace("\\\"", "\"").replace("\\\\", "\\"))
+    } else if let Ok(v) = s.parse() {
+        FieldValue::U64(v)
+    } else if let Ok(v) = s.parse() {
+        FieldValue::I64(v)
+    } else if let Ok(v) = s.parse() {
+        FieldValue::F64(v)
+    } else if let Ok(v) = s.parse() {
+        FieldValue::Bool(v)
+    } else {
+        FieldValue::Debug(s.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(event: &CollectedEvent) -> &FieldValue {
+        &event.fields["message"]
+    }
+
+    #[test]
+    fn parses_default_format() {
+        let event = parse_line(
+            "2024-05-01T12:00:00.123456Z  INFO my_app::http: request done status=200 ok=true",
+        )
+        .unwrap();
+        assert_eq!(event.level, Level::INFO);
+        assert_eq!(event.target, "my_app::http");
+        assert_eq!(
+            event.time,
+            DateTime::parse_from_rfc3339("2024-05-01T12:00:00.123456Z").unwrap()
+        );
+        assert_eq!(message(&event), &FieldValue::Str("request done".to_owned()));
+        assert_eq!(event.fields["status"], FieldValue::U64(200));
+        assert_eq!(event.fields["ok"], FieldValue::Bool(true));
+        assert!(event.spans.is_empty());
+    }
+
+    #[test]
+    fn parses_lines_without_time() {
+        let event = parse_line("WARN my_app: careful latency=-1.5").unwrap();
+        assert_eq!(event.level, Level::WARN);
+        assert_eq!(event.target, "my_app");
+        assert_eq!(event.fields["latency"], FieldValue::F64(-1.5));
+    }
+
+    #[test]
+    fn strips_ansi_colors() {
+        let line = "\x1b[2m2024-05-01T12:00:00Z\x1b[0m \x1b[32m INFO\x1b[0m \
+                    \x1b[1mrequest\x1b[0m\x1b[1m{\x1b[0m\x1b[3mpath\x1b[0m\x1b[2m=\x1b[0m\"/\"\x1b[1m}\x1b[0m\x1b[2m:\x1b[0m \
+                    \x1b[2mmy_app\x1b[0m\x1b[2m:\x1b[0m hello";
+        let stripped = strip_ansi(line);
+        assert_eq!(
+            stripped,
+            "2024-05-01T12:00:00Z  INFO request{path=\"/\"}: my_app: hello"
+        );
+        let event = parse_line(&stripped).unwrap();
+        assert_eq!(event.target, "my_app");
+        assert_eq!(message(&event), &FieldValue::Str("hello".to_owned()));
+        assert_eq!(strip_ansi("no colors"), "no colors");
+    }
+
+    #[test]
+    fn parses_span_prefixes() {
+        let event = parse_line(
+            "2024-05-01T12:00:00Z DEBUG conn{id=7}:request{path=\"/a b\" method=GET}:db: my_app::db: query rows=3",
+        )
+        .unwrap();
+        assert_eq!(event.target, "my_app::db");
+        let names: Vec<_> = event.spans.iter().map(|span| span.name.as_str()).collect();
+        assert_eq!(names, ["conn", "request", "db"]);
+        assert_eq!(event.spans[0].fields["id"], FieldValue::U64(7));
+        assert_eq!(
+            event.spans[1].fields["path"],
+            FieldValue::Str("/a b".to_owned())
+        );
+        assert_eq!(
+            event.spans[1].fields["method"],
+            FieldValue::Debug("GET".to_owned())
+        );
+        assert!(event.spans[2].fields.is_empty());
+        assert_eq!(message(&event), &FieldValue::Str("query".to_owned()));
+        assert_eq!(event.fields["rows"], FieldValue::U64(3));
+    }
+
+    #[test]
+    fn continues_multi_line_messages() {
+        let mut event = parse_line("2024-05-01T12:00:00Z ERROR my_app: failed:").unwrap();
+        assert!(parse_line("  caused by: timeout").is_none());
+        append_line(&mut event, "  caused by: timeout");
+        append_line(&mut event, "  at src/main.rs:3");
+        assert_eq!(
+            message(&event),
+            &FieldValue::Str("failed:\n  caused by: timeout\n  at src/main.rs:3".to_owned())
+        );
+
+        let mut event = parse_line("2024-05-01T12:00:00Z ERROR my_app: id=1").unwrap();
+        append_line(&mut event, "details");
+        assert_eq!(message(&event), &FieldValue::Str("details".to_owned()));
+    }
+
+    #[test]
+    fn rejects_unparseable_lines() {
+        assert!(parse_line("").is_none());
+        assert!(parse_line("   ").is_none());
+        assert!(parse_line("hello world").is_none());
+        assert!(parse_line("2024-05-01T12:00:00Z").is_none());
+        assert!(parse_line("2024-05-01T12:00:00Z NOTICE my_app: hi").is_none());
+        assert!(parse_line("info my_app: lowercase levels aren't written").is_none());
+    }
+}
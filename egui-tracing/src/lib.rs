@@ -6,7 +6,7 @@ pub mod tracing;
 pub mod ui;
 
 pub use self::tracing::EventCollector;
-pub use self::ui::Logs;
+pub use self::ui::{Logs, Spans};
 
 #[cfg(feature = "reexport")]
 pub use egui;
@@ -4,10 +4,10 @@ pub trait Ellipse {
     fn truncate_graphemes(&self, len: usize) -> String;
 }
 
-impl Ellipse for String {
+impl Ellipse for str {
     fn truncate_graphemes(&self, len: usize) -> String {
         if self.len() <= len {
-            return self.clone();
+            return self.to_owned();
         }
 
         let mut trucated = self
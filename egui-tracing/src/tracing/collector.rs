@@ -1,13 +1,24 @@
-use std::sync::{Arc, Mutex};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
 
-use tracing::{Event, Level, Subscriber};
+use chrono::{DateTime, Local};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
 #[cfg(feature = "log")]
 use tracing_log::NormalizeEvent;
-use tracing_subscriber::layer::Context;
-use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::layer::{Context, Filter};
+use tracing_subscriber::registry::{LookupSpan, SpanRef};
 use tracing_subscriber::Layer;
 
-use super::event::CollectedEvent;
+use super::directive::{DirectiveError, Directives};
+use super::event::{CollectedEvent, SpanContext, ThreadInfo};
+use super::field::{FieldValue, FieldVisitor};
+use super::repaint::Repainter;
+use super::span::CollectedSpan;
+use super::store::{EventsGuard, Retention, SpansGuard, Store, StoreGuard};
 
 #[derive(Clone, Debug)]
 pub enum AllowedTargets {
@@ -15,11 +26,65 @@ pub enum AllowedTargets {
     Selected(Vec<String>),
 }
 
+/// What an [`EventCollector`] captures. Shared between the clones of a
+/// collector, so it can be changed while the application is running.
+#[derive(Debug, Clone)]
+pub struct CaptureConfig {
+    pub allowed_targets: AllowedTargets,
+    /// Maximum level of targets without a directive.
+    pub level: Level,
+    pub directives: Directives,
+}
+
+impl CaptureConfig {
+    pub fn max_level(&self, target: &str) -> LevelFilter {
+        self.directives
+            .max_level(target)
+            .unwrap_or_else(|| self.level.into())
+    }
+
+    pub fn is_enabled(&self, level: &Level, target: &str) -> bool {
+        if *level > self.max_level(target) {
+            return false;
+        }
+        match self.allowed_targets {
+            AllowedTargets::All => true,
+            AllowedTargets::Selected(ref selection) => {
+                selection.iter().any(|allowed| target.starts_with(allowed))
+            }
+        }
+    }
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            allowed_targets: AllowedTargets::All,
+            level: Level::TRACE, // capture everything by default.
+            directives: Directives::default(),
+        }
+    }
+}
+
+/// A [`Layer`] collecting events and spans for display in the UI.
+///
+/// Events and spans that the [`CaptureConfig`] rejects are skipped before any
+/// of their fields are recorded. To not even dispatch them to the collector,
+/// add it with its [`CaptureFilter`], which only applies to this layer:
+///
+/// ```ignore
+/// tracing_subscriber::registry()
+///     .with(collector.clone().with_filter(collector.capture_filter()))
+///     .init();
+/// ```
 #[derive(Debug, Clone)]
 pub struct EventCollector {
-    allowed_targets: AllowedTargets,
-    level: Level,
-    events: Arc<Mutex<Vec<CollectedEvent>>>,
+    config: Arc<RwLock<CaptureConfig>>,
+    retention: Retention,
+    span_retention: Retention,
+    repainter: Arc<Repainter>,
+    events: Arc<Mutex<Store<CollectedEvent>>>,
+    spans: Arc<Mutex<Store<CollectedSpan>>>,
 }
 
 impl EventCollector {
@@ -28,62 +93,480 @@ impl EventCollector {
     }
 
     pub fn with_level(self, level: Level) -> Self {
-        Self { level, ..self }
+        self.set_level(level);
+        self
     }
 
     pub fn allowed_targets(self, allowed_targets: AllowedTargets) -> Self {
+        self.set_allowed_targets(allowed_targets);
+        self
+    }
+
+    /// Sets per-target maximum levels, falling back to the level set with
+    /// [`EventCollector::with_level`] for targets without a directive.
+    ///
+    /// ```
+    /// # use egui_tracing::EventCollector;
+    /// let collector = EventCollector::new()
+    ///     .with_directives("info,my_app=trace,hyper=warn".parse().unwrap());
+    /// ```
+    pub fn with_directives(self, directives: Directives) -> Self {
+        self.set_directives(directives);
+        self
+    }
+
+    /// Reads the directives from the environment variable `var`, usually
+    /// `RUST_LOG`. See [`EventCollector::with_directives`].
+    pub fn with_env_directives(self, var: &str) -> Result<Self, DirectiveError> {
+        Ok(self.with_directives(Directives::from_env(var)?))
+    }
+
+    /// Keeps at most `max_events` events, evicting the oldest ones first.
+    pub fn with_max_events(self, max_events: usize) -> Self {
+        Self {
+            retention: Retention {
+                max_len: Some(max_events),
+                ..self.retention
+            },
+            ..self
+        }
+    }
+
+    /// Keeps roughly at most `max_bytes` worth of events, evicting the oldest ones first.
+    pub fn with_max_bytes(self, max_bytes: usize) -> Self {
         Self {
-            allowed_targets,
+            retention: Retention {
+                max_bytes: Some(max_bytes),
+                ..self.retention
+            },
             ..self
         }
     }
 
+    /// Keeps at most `max_spans` spans, evicting the oldest ones first. Defaults
+    /// to 10000, as spans are collected for the lifetime of the application.
+    pub fn with_max_spans(self, max_spans: usize) -> Self {
+        Self {
+            span_retention: Retention {
+                max_len: Some(max_spans),
+                ..self.span_retention
+            },
+            ..self
+        }
+    }
+
+    /// Requests a repaint of `ctx` when events arrive, so the logs stay up to
+    /// date in otherwise idle applications.
+    pub fn set_egui_context(&self, ctx: egui::Context) {
+        self.repainter.set_context(ctx);
+    }
+
+    /// Requests at most one repaint per `interval`, defaults to 100ms. See
+    /// [`EventCollector::set_egui_context`].
+    pub fn with_repaint_interval(self, interval: Duration) -> Self {
+        self.repainter.set_interval(interval);
+        self
+    }
+
+    pub fn capture_config(&self) -> CaptureConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Replaces the capture configuration of this collector and all its clones.
+    pub fn set_capture_config(&self, config: CaptureConfig) {
+        self.update_config(|current| *current = config);
+    }
+
+    pub fn set_level(&self, level: Level) {
+        self.update_config(|config| config.level = level);
+    }
+
+    pub fn set_allowed_targets(&self, allowed_targets: AllowedTargets) {
+        self.update_config(|config| config.allowed_targets = allowed_targets);
+    }
+
+    pub fn set_directives(&self, directives: Directives) {
+        self.update_config(|config| config.directives = directives);
+    }
+
+    /// A per-layer [`Filter`] for this collector, following changes to its
+    /// capture config.
+    pub fn capture_filter(&self) -> CaptureFilter {
+        CaptureFilter {
+            config: self.config.clone(),
+        }
+    }
+
     pub fn events(&self) -> Vec<CollectedEvent> {
-        self.events.lock().unwrap().clone()
+        self.read().iter().map(|(_, event)| event.clone()).collect()
+    }
+
+    /// Returns the events collected since `cursor` together with the cursor to
+    /// pass on the next call. Start with a cursor of `0`.
+    pub fn events_since(&self, cursor: u64) -> (Vec<CollectedEvent>, u64) {
+        let events = self.read();
+        let new_events = events
+            .iter_since(cursor)
+            .map(|(_, event)| event.clone())
+            .collect();
+        (new_events, events.next_seq())
+    }
+
+    /// Locks the event store for reading without cloning any events.
+    pub fn read(&self) -> EventsGuard<'_> {
+        StoreGuard(self.events.lock().unwrap())
     }
 
+    pub fn spans(&self) -> Vec<CollectedSpan> {
+        self.read_spans()
+            .iter()
+            .map(|(_, span)| span.clone())
+            .collect()
+    }
+
+    /// Locks the span store for reading without cloning any spans.
+    pub fn read_spans(&self) -> SpansGuard<'_> {
+        StoreGuard(self.spans.lock().unwrap())
+    }
+
+    /// Identifies the event store, which is shared by the clones of this
+    /// collector.
+    pub(crate) fn id(&self) -> usize {
+        Arc::as_ptr(&self.events) as usize
+    }
+
+    /// Number of events evicted by the retention limits since the last
+    /// [`EventCollector::clear`].
+    pub fn evicted_events(&self) -> u64 {
+        self.read().evicted()
+    }
+
+    /// Number of events dropped before they were stored, e.g. by a
+    /// [`RemoteSender`](super::RemoteSender), since the last
+    /// [`EventCollector::clear`].
+    pub fn dropped_events(&self) -> u64 {
+        self.read().dropped()
+    }
+
+    /// Adds an event that wasn't recorded by this collector, e.g. one read
+    /// from a log file. It bypasses the capture config.
+    pub fn import(&self, event: CollectedEvent) {
+        self.events.lock().unwrap().push(event, &self.retention);
+        self.repainter.request();
+    }
+
+    /// Removes all collected events and spans.
     pub fn clear(&self) {
-        let mut events = self.events.lock().unwrap();
-        *events = Vec::new();
-    }
-
-    fn collect(&self, event: CollectedEvent) {
-        if event.level <= self.level {
-            let should_collect = match self.allowed_targets {
-                AllowedTargets::All => true,
-                AllowedTargets::Selected(ref selection) => selection
-                    .iter()
-                    .any(|target| event.target.starts_with(target)),
-            };
-            if should_collect {
-                self.events.lock().unwrap().push(event);
-            }
-        }
+        self.events.lock().unwrap().clear();
+        self.clear_spans();
+    }
+
+    pub fn clear_spans(&self) {
+        self.spans.lock().unwrap().clear();
+    }
+
+    fn update_config(&self, f: impl FnOnce(&mut CaptureConfig)) {
+        f(&mut self.config.write().unwrap());
+        // Callsites cache whether they are enabled, which may have changed.
+        // The lock has to be released first, as rebuilding calls back into
+        // the layer.
+        tracing::callsite::rebuild_interest_cache();
+    }
+
+    pub(super) fn should_collect(&self, level: &Level, target: &str) -> bool {
+        self.config.read().unwrap().is_enabled(level, target)
+    }
+
+    pub(super) fn collect(&self, event: CollectedEvent) {
+        self.events.lock().unwrap().push(event, &self.retention);
+        self.repainter.request();
+    }
+
+    /// Counts events that were dropped before reaching this collector.
+    pub(super) fn add_dropped(&self, count: u64) {
+        self.events.lock().unwrap().add_dropped(count);
+    }
+
+    fn update_span(&self, seq: u64, f: impl FnOnce(&mut CollectedSpan)) {
+        self.spans.lock().unwrap().update(seq, f);
     }
 }
 
 impl Default for EventCollector {
     fn default() -> Self {
         Self {
-            allowed_targets: AllowedTargets::All,
-            events: Arc::new(Mutex::new(Vec::new())),
-            level: Level::TRACE, // capture everything by default.
+            config: Arc::new(RwLock::new(CaptureConfig::default())),
+            events: Arc::new(Mutex::new(Store::default())),
+            spans: Arc::new(Mutex::new(Store::default())),
+            retention: Retention::default(),
+            span_retention: Retention {
+                max_len: Some(10_000),
+                max_bytes: None,
+            },
+            repainter: Arc::new(Repainter::default()),
         }
     }
 }
 
+/// Disables what the [`CaptureConfig`] of an [`EventCollector`] rejects for
+/// that collector only, see [`EventCollector::capture_filter`].
+#[derive(Debug, Clone)]
+pub struct CaptureFilter {
+    config: Arc<RwLock<CaptureConfig>>,
+}
+
+impl<S> Filter<S> for CaptureFilter {
+    fn enabled(&self, meta: &Metadata<'_>, _ctx: &Context<'_, S>) -> bool {
+        // Events converted from `log` records are checked here with the
+        // record's target, and then dispatched through a callsite per level.
+        self.config
+            .read()
+            .unwrap()
+            .is_enabled(meta.level(), meta.target())
+    }
+
+    fn callsite_enabled(&self, meta: &'static Metadata<'static>) -> Interest {
+        // The result is cached per callsite until the capture config changes
+        // and `rebuild_interest_cache` is called.
+        if self
+            .config
+            .read()
+            .unwrap()
+            .is_enabled(meta.level(), meta.target())
+        {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        let config = self.config.read().unwrap();
+        let default = config
+            .directives
+            .default_level()
+            .unwrap_or_else(|| config.level.into());
+        config
+            .directives
+            .targets()
+            .map(|(_, level)| level)
+            .chain([default])
+            .max()
+    }
+}
+
 impl<S> Layer<S> for EventCollector
 where
     S: Subscriber + for<'a> LookupSpan<'a>,
 {
-    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
-        #[cfg(feature = "log")]
-        let normalized_meta = event.normalized_metadata();
-        #[cfg(feature = "log")]
-        let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
-        #[cfg(not(feature = "log"))]
-        let meta = event.metadata();
-
-        self.collect(CollectedEvent::new(event, meta));
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = BTreeMap::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+
+        let meta = span.metadata();
+        if self.should_collect(meta.level(), meta.target()) {
+            let now = Local::now();
+            let parent = span
+                .parent()
+                .and_then(|parent| parent.extensions().get::<SpanTiming>().map(|t| t.seq));
+            let seq = self.spans.lock().unwrap().push(
+                CollectedSpan {
+                    id: id.into_u64(),
+                    parent,
+                    name: meta.name().to_owned(),
+                    target: meta.target().to_owned(),
+                    level: meta.level().to_owned(),
+                    fields: fields.clone(),
+                    opened: now,
+                    closed: None,
+                    busy: Duration::ZERO,
+                    idle: Duration::ZERO,
+                    thread: None,
+                },
+                &self.span_retention,
+            );
+            span.extensions_mut().insert(SpanTiming { seq, last: now });
+        }
+
+        insert_span_fields(&span, fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        record_span_fields(&span, values);
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<SpanTiming>() {
+            self.update_span(timing.seq, |span| {
+                values.record(&mut FieldVisitor(&mut span.fields));
+            });
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<SpanTiming>() {
+            let now = Local::now();
+            let idle = timing.elapsed(now);
+            self.update_span(timing.seq, |span| {
+                span.idle += idle;
+                if span.thread.is_none() {
+                    span.thread = Some(ThreadInfo::current());
+                }
+            });
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<SpanTiming>() {
+            let busy = timing.elapsed(Local::now());
+            self.update_span(timing.seq, |span| span.busy += busy);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(timing) = extensions.get_mut::<SpanTiming>() {
+            let now = Local::now();
+            let idle = timing.elapsed(now);
+            self.update_span(timing.seq, |span| {
+                span.idle += idle;
+                span.closed = Some(now);
+            });
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if let Some(event) = build_event(event, &ctx, |meta| {
+            self.should_collect(meta.level(), meta.target())
+        }) {
+            self.collect(event);
+        }
+    }
+}
+
+/// Builds the collected event, unless `filter` rejects its metadata.
+pub(super) fn build_event<S>(
+    event: &Event<'_>,
+    ctx: &Context<'_, S>,
+    filter: impl FnOnce(&Metadata<'_>) -> bool,
+) -> Option<CollectedEvent>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    #[cfg(feature = "log")]
+    let normalized_meta = event.normalized_metadata();
+    #[cfg(feature = "log")]
+    let meta = normalized_meta.as_ref().unwrap_or_else(|| event.metadata());
+    #[cfg(not(feature = "log"))]
+    let meta = event.metadata();
+
+    if !filter(meta) {
+        return None;
+    }
+
+    let spans = ctx
+        .event_scope(event)
+        .map(|scope| {
+            scope
+                .from_root()
+                .map(|span| SpanContext {
+                    id: span.id().into_u64(),
+                    name: span.name().to_owned(),
+                    target: span.metadata().target().to_owned(),
+                    level: span.metadata().level().to_owned(),
+                    fields: span
+                        .extensions()
+                        .get::<SpanFields>()
+                        .map(|SpanFields(fields)| fields.clone())
+                        .unwrap_or_default(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(CollectedEvent {
+        spans,
+        ..CollectedEvent::new(event, meta)
+    })
+}
+
+/// Stores the fields of a new span for the events in it. Other layers of this
+/// crate in the same subscriber may have done so already.
+pub(super) fn insert_span_fields<S>(span: &SpanRef<'_, S>, fields: BTreeMap<String, FieldValue>)
+where
+    S: for<'a> LookupSpan<'a>,
+{
+    let mut extensions = span.extensions_mut();
+    if extensions.get_mut::<SpanFields>().is_none() {
+        extensions.insert(SpanFields(fields));
+    }
+}
+
+pub(super) fn record_span_fields<S>(span: &SpanRef<'_, S>, values: &Record<'_>)
+where
+    S: for<'a> LookupSpan<'a>,
+{
+    if let Some(SpanFields(fields)) = span.extensions_mut().get_mut::<SpanFields>() {
+        values.record(&mut FieldVisitor(fields));
+    }
+}
+
+/// Fields recorded on a span, stored in the span's extensions.
+struct SpanFields(BTreeMap<String, FieldValue>);
+
+/// Links a collected span to its entry in the span store.
+struct SpanTiming {
+    seq: u64,
+    last: DateTime<Local>,
+}
+
+impl SpanTiming {
+    /// Returns the time since the last transition and starts a new one at `now`.
+    fn elapsed(&mut self, now: DateTime<Local>) -> Duration {
+        let elapsed = now.signed_duration_since(self.last).to_std();
+        self.last = now;
+        elapsed.unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::Registry;
+
+    use super::*;
+
+    fn max_level_hint(collector: &EventCollector) -> Option<LevelFilter> {
+        Filter::<Registry>::max_level_hint(&collector.capture_filter())
+    }
+
+    #[test]
+    fn max_level_hint_prefers_the_default_directive() {
+        let collector = EventCollector::new();
+        assert_eq!(max_level_hint(&collector), Some(LevelFilter::TRACE));
+
+        collector.set_directives(Directives::parse("warn,my_app=debug").unwrap());
+        assert_eq!(max_level_hint(&collector), Some(LevelFilter::DEBUG));
+
+        collector.set_directives(Directives::parse("my_app=debug").unwrap());
+        collector.set_level(Level::INFO);
+        assert_eq!(max_level_hint(&collector), Some(LevelFilter::DEBUG));
+
+        collector.set_directives(Directives::parse("error").unwrap());
+        assert_eq!(max_level_hint(&collector), Some(LevelFilter::ERROR));
     }
 }
@@ -0,0 +1,227 @@
+use std::cmp::Reverse;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use tracing::level_filters::LevelFilter;
+
+/// Per-target maximum levels in the format of `RUST_LOG`, e.g.
+/// `info,my_app=trace,hyper=warn`.
+///
+/// A directive is either a bare level, setting the default, or
+/// `target=level`, applying to every target starting with `target`. The
+/// longest matching target wins. A target without a level enables all levels.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Directives {
+    default: Option<LevelFilter>,
+    /// Sorted by descending target length, so the first match is the longest.
+    targets: Vec<(String, LevelFilter)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectiveError {
+    pub directive: String,
+    pub message: String,
+}
+
+impl Display for DirectiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid directive `{}`: {}",
+            self.directive, self.message
+        )
+    }
+}
+
+impl std::error::Error for DirectiveError {}
+
+impl Directives {
+    pub fn parse(input: &str) -> Result<Self, DirectiveError> {
+        let mut directives = Self::default();
+        for directive in input.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let error = |message: &str| DirectiveError {
+                directive: directive.to_owned(),
+                message: message.to_owned(),
+            };
+            if directive.contains(['[', ']', '{', '}']) {
+                return Err(error("span filters are not supported"));
+            }
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    let target = target.trim();
+                    if target.is_empty() {
+                        return Err(error("expected a target before `=`"));
+                    }
+                    // `LevelFilter` parses an empty string as `error`.
+                    let level = Some(level.trim())
+                        .filter(|level| !level.is_empty())
+                        .and_then(|level| level.parse().ok())
+                        .ok_or_else(|| error("expected a level after `=`"))?;
+                    directives.set(target, level);
+                }
+                None => match directive.parse() {
+                    Ok(level) => directives.default = Some(level),
+                    Err(_) => directives.set(directive, LevelFilter::TRACE),
+                },
+            }
+        }
+        Ok(directives)
+    }
+
+    /// Parses the directives in the environment variable `var`, returning no
+    /// directives if it isn't set.
+    pub fn from_env(var: &str) -> Result<Self, DirectiveError> {
+        std::env::var(var).map_or_else(|_| Ok(Self::default()), |value| Self::parse(&value))
+    }
+
+    /// Sets the maximum level of `target` and all targets starting with it.
+    pub fn set(&mut self, target: &str, level: LevelFilter) {
+        match self.targets.iter_mut().find(|(t, _)| t == target) {
+            Some((_, l)) => *l = level,
+            None => {
+                self.targets.push((target.to_owned(), level));
+                self.targets
+                    .sort_by_key(|(target, _)| Reverse(target.len()));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, target: &str) {
+        self.targets.retain(|(t, _)| t != target);
+    }
+
+    pub fn set_default(&mut self, level: Option<LevelFilter>) {
+        self.default = level;
+    }
+
+    pub fn default_level(&self) -> Option<LevelFilter> {
+        self.default
+    }
+
+    pub fn targets(&self) -> impl Iterator<Item = (&str, LevelFilter)> {
+        self.targets
+            .iter()
+            .map(|(target, level)| (target.as_str(), *level))
+    }
+
+    /// Maximum level of `target`, or `None` if no directive applies to it.
+    pub fn max_level(&self, target: &str) -> Option<LevelFilter> {
+        self.targets
+            .iter()
+            .find(|(prefix, _)| target.starts_with(prefix.as_str()))
+            .map(|(_, level)| *level)
+            .or(self.default)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.default.is_none() && self.targets.is_empty()
+    }
+}
+
+impl FromStr for Directives {
+    type Err = DirectiveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for Directives {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+        if let Some(level) = self.default {
+            write!(f, "{}", level)?;
+            separator = ",";
+        }
+        for (target, level) in &self.targets {
+            write!(f, "{}{}={}", separator, target, level)?;
+            separator = ",";
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Directives {
+        Directives::parse(input).unwrap()
+    }
+
+    #[test]
+    fn most_specific_target_wins() {
+        let directives = parse("my_app::db=trace,info,my_app=warn");
+        assert_eq!(
+            directives.max_level("my_app::db::pool"),
+            Some(LevelFilter::TRACE)
+        );
+        assert_eq!(
+            directives.max_level("my_app::http"),
+            Some(LevelFilter::WARN)
+        );
+        assert_eq!(directives.max_level("my_app"), Some(LevelFilter::WARN));
+        assert_eq!(directives.max_level("hyper"), Some(LevelFilter::INFO));
+    }
+
+    #[test]
+    fn later_directives_override_earlier_ones() {
+        let directives = parse("debug,hyper=warn,hyper=error,trace");
+        assert_eq!(directives.default_level(), Some(LevelFilter::TRACE));
+        assert_eq!(directives.max_level("hyper"), Some(LevelFilter::ERROR));
+        assert_eq!(directives.targets().count(), 1);
+    }
+
+    #[test]
+    fn bare_levels_and_targets() {
+        let directives = parse("WARN");
+        assert_eq!(directives.default_level(), Some(LevelFilter::WARN));
+        assert_eq!(directives.max_level("anything"), Some(LevelFilter::WARN));
+
+        // A bare target enables all its levels.
+        let directives = parse("my_app");
+        assert_eq!(directives.default_level(), None);
+        assert_eq!(directives.max_level("my_app::db"), Some(LevelFilter::TRACE));
+        assert_eq!(directives.max_level("hyper"), None);
+    }
+
+    #[test]
+    fn off() {
+        let directives = parse("off,my_app=off,my_app::db=info");
+        assert_eq!(directives.default_level(), Some(LevelFilter::OFF));
+        assert_eq!(directives.max_level("hyper"), Some(LevelFilter::OFF));
+        assert_eq!(directives.max_level("my_app"), Some(LevelFilter::OFF));
+        assert_eq!(directives.max_level("my_app::db"), Some(LevelFilter::INFO));
+    }
+
+    #[test]
+    fn whitespace_and_empty_directives() {
+        assert_eq!(
+            parse(" info , my_app = debug ,, "),
+            parse("info,my_app=debug")
+        );
+        assert!(parse("").is_empty());
+        assert!(parse(" , ").is_empty());
+    }
+
+    #[test]
+    fn invalid_directives() {
+        let err = Directives::parse("info,=warn").unwrap_err();
+        assert_eq!(err.directive, "=warn");
+        assert_eq!(err.message, "expected a target before `=`");
+
+        let err = Directives::parse("my_app=loud").unwrap_err();
+        assert_eq!(err.directive, "my_app=loud");
+        assert_eq!(err.message, "expected a level after `=`");
+
+        assert!(Directives::parse("my_app=").is_err());
+        assert!(Directives::parse("my_app[request]=debug").is_err());
+        assert!(Directives::parse("my_app[{id=1}]").is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let directives = parse("info,my_app=debug,my_app::db=off");
+        assert_eq!(directives.to_string().parse::<Directives>(), Ok(directives));
+    }
+}
@@ -1,16 +1,40 @@
 use std::collections::BTreeMap;
-use std::fmt::Debug;
+use std::fmt::{self, Display};
 
 use chrono::{DateTime, Local};
-use tracing::field::{Field, Visit};
 use tracing::{Event, Metadata};
 
+use super::field::{fields_size, FieldValue, FieldVisitor};
+use super::store::ApproxSize;
+
 #[derive(Debug, Clone)]
 pub struct CollectedEvent {
     pub target: String,
     pub level: tracing::Level,
-    pub fields: BTreeMap<String, String>,
+    pub fields: BTreeMap<String, FieldValue>,
     pub time: DateTime<Local>,
+    /// Spans the event was emitted in, from the root to the innermost one.
+    pub spans: Vec<SpanContext>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub module_path: Option<String>,
+    pub thread: ThreadInfo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ThreadInfo {
+    pub name: Option<String>,
+    /// Debug representation of the [`std::thread::ThreadId`].
+    pub id: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct SpanContext {
+    pub id: u64,
+    pub name: String,
+    pub target: String,
+    pub level: tracing::Level,
+    pub fields: BTreeMap<String, FieldValue>,
 }
 
 impl CollectedEvent {
@@ -23,15 +47,89 @@ impl CollectedEvent {
             time: Local::now(),
             target: meta.target().to_owned(),
             fields,
+            spans: Vec::new(),
+            file: meta.file().map(str::to_owned),
+            line: meta.line(),
+            module_path: meta.module_path().map(str::to_owned),
+            thread: ThreadInfo::current(),
         }
     }
+
+    /// Formats the source location like `src/main.rs:42`.
+    pub fn location(&self) -> Option<String> {
+        let file = self.file.as_ref()?;
+        Some(match self.line {
+            Some(line) => format!("{}:{}", file, line),
+            None => file.clone(),
+        })
+    }
+
+    /// Formats the span chain like `request{id=42}:db_query`.
+    pub fn span_breadcrumb(&self) -> String {
+        self.spans
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(":")
+    }
 }
 
-struct FieldVisitor<'a>(&'a mut BTreeMap<String, String>);
+impl ApproxSize for CollectedEvent {
+    fn approx_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.target.len()
+            + fields_size(&self.fields)
+            + self.file.as_ref().map_or(0, String::len)
+            + self.module_path.as_ref().map_or(0, String::len)
+            + self.thread.approx_size()
+            + self
+                .spans
+                .iter()
+                .map(SpanContext::approx_size)
+                .sum::<usize>()
+    }
+}
 
-impl<'a> Visit for FieldVisitor<'a> {
-    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
-        self.0
-            .insert(field.name().to_string(), format!("{:?}", value));
+impl SpanContext {
+    fn approx_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.name.len()
+            + self.target.len()
+            + fields_size(&self.fields)
+    }
+}
+
+impl ThreadInfo {
+    pub fn current() -> Self {
+        let thread = std::thread::current();
+        Self {
+            name: thread.name().map(str::to_owned),
+            id: format!("{:?}", thread.id()),
+        }
+    }
+
+    pub(crate) fn approx_size(&self) -> usize {
+        self.name.as_ref().map_or(0, String::len) + self.id.len()
+    }
+}
+
+impl Display for ThreadInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name.as_ref().unwrap_or(&self.id))
+    }
+}
+
+impl Display for SpanContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if !self.fields.is_empty() {
+            let fields = self
+                .fields
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>();
+            write!(f, "{{{}}}", fields.join(" "))?;
+        }
+        Ok(())
     }
 }
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt::{self, Debug, Display};
+
+use tracing::field::{Field, Visit};
+
+/// A recorded field value, keeping the type it was recorded with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Bool(bool),
+    Str(String),
+    /// A value only available through its [`Debug`] representation.
+    Debug(String),
+    Error {
+        message: String,
+        /// Messages of the error's [`Error::source`] chain, outermost first.
+        sources: Vec<String>,
+    },
+}
+
+impl FieldValue {
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::I64(v) => Some(v),
+            Self::U64(v) => i64::try_from(v).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Self::I64(v) => u64::try_from(v).ok(),
+            Self::U64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns any numeric value as a float.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Self::I64(v) => Some(v as f64),
+            Self::U64(v) => Some(v as f64),
+            Self::F64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Self::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn approx_size(&self) -> usize {
+        match self {
+            Self::Str(v) | Self::Debug(v) => v.len(),
+            Self::Error { message, sources } => {
+                message.len() + sources.iter().map(String::len).sum::<usize>()
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::I64(v) => Display::fmt(v, f),
+            Self::U64(v) => Display::fmt(v, f),
+            Self::F64(v) => Display::fmt(v, f),
+            Self::Bool(v) => Display::fmt(v, f),
+            Self::Str(v) | Self::Debug(v) => f.write_str(v),
+            Self::Error { message, sources } => {
+                f.write_str(message)?;
+                for source in sources {
+                    write!(f, ": {}", source)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+pub(crate) fn fields_size(fields: &BTreeMap<String, FieldValue>) -> usize {
+    fields
+        .iter()
+        .map(|(key, value)| key.len() + std::mem::size_of::<FieldValue>() + value.approx_size())
+        .sum()
+}
+
+pub(crate) struct FieldVisitor<'a>(pub(crate) &'a mut BTreeMap<String, FieldValue>);
+
+impl<'a> FieldVisitor<'a> {
+    fn insert(&mut self, field: &Field, value: FieldValue) {
+        self.0.insert(field.name().to_string(), value);
+    }
+}
+
+impl<'a> Visit for FieldVisitor<'a> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, FieldValue::F64(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, FieldValue::I64(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, FieldValue::U64(value));
+    }
+
+    fn record_i128(&mut self, field: &Field, value: i128) {
+        match i64::try_from(value) {
+            Ok(value) => self.record_i64(field, value),
+            Err(_) => self.record_debug(field, &value),
+        }
+    }
+
+    fn record_u128(&mut self, field: &Field, value: u128) {
+        match u64::try_from(value) {
+            Ok(value) => self.record_u64(field, value),
+            Err(_) => self.record_debug(field, &value),
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, FieldValue::Bool(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, FieldValue::Str(value.to_owned()));
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn Error + 'static)) {
+        let mut sources = Vec::new();
+        let mut source = value.source();
+        while let Some(error) = source {
+            sources.push(error.to_string());
+            source = error.source();
+        }
+        self.insert(
+            field,
+            FieldValue::Error {
+                message: value.to_string(),
+                sources,
+            },
+        );
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.insert(field, FieldValue::Debug(format!("{:?}", value)));
+    }
+}
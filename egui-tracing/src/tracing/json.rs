@@ -0,0 +1,392 @@
+//! JSON Lines export and import of collected events, one object per line in
+//! the format of `tracing_subscriber`'s JSON formatter:
+//!
+//! ```json
+//! {"timestamp":"2024-05-01T12:00:00.000000+02:00","level":"INFO","fields":{"message":"hello","id":42},"target":"my_app","filename":"src/main.rs","line_number":12,"module_path":"my_app","threadName":"main","threadId":"ThreadId(1)","spans":[{"name":"request","path":"/"}]}
+//! ```
+//!
+//! Logs written by `tracing_subscriber::fmt().json()` can be imported as well,
+//! including ones with flattened fields. Events without an RFC 3339 timestamp,
+//! e.g. from an uptime timer, get the time they are imported at and keep the
+//! original timestamp in a `timestamp` field.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+use chrono::{DateTime, Local, SecondsFormat};
+use serde::{Deserialize, Serialize};
+use serde_json::{Number, Value};
+
+use super::collector::EventCollector;
+use super::event::{CollectedEvent, SpanContext, ThreadInfo};
+use super::field::FieldValue;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timestamp: Option<String>,
+    level: String,
+    #[serde(default)]
+    fields: BTreeMap<String, Value>,
+    target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_number: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    module_path: Option<String>,
+    #[serde(rename = "threadName", skip_serializing_if = "Option::is_none")]
+    thread_name: Option<String>,
+    #[serde(rename = "threadId", skip_serializing_if = "Option::is_none")]
+    thread_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    spans: Vec<JsonSpan>,
+    /// Any other keys, such as fields flattened into the event or the
+    /// current `span`.
+    #[serde(flatten)]
+    other: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonSpan {
+    name: String,
+    #[serde(flatten)]
+    fields: BTreeMap<String, Value>,
+}
+
+impl From<&CollectedEvent> for JsonEvent {
+    fn from(event: &CollectedEvent) -> Self {
+        Self {
+            timestamp: Some(format_timestamp(&event.time)),
+            level: event.level.to_string(),
+            fields: to_json_fields(&event.fields),
+            target: event.target.clone(),
+            filename: event.file.clone(),
+            line_number: event.line,
+            module_path: event.module_path.clone(),
+            thread_name: event.thread.name.clone(),
+            thread_id: Some(event.thread.id.clone()),
+            spans: event.spans.iter().map(JsonSpan::from).collect(),
+            other: BTreeMap::new(),
+        }
+    }
+}
+
+impl From<&SpanContext> for JsonSpan {
+    fn from(span: &SpanContext) -> Self {
+        Self {
+            name: span.name.clone(),
+            fields: to_json_fields(&span.fields),
+        }
+    }
+}
+
+impl JsonEvent {
+    fn into_event(self) -> Result<CollectedEvent, String> {
+        let level = self
+            .level
+            .parse()
+            .map_err(|_| format!("unknown level `{}`", self.level))?;
+        let mut fields = self.fields;
+        if fields.is_empty() {
+            fields = self.other;
+            fields.remove("span");
+        }
+        let mut fields = from_json_fields(fields);
+        let time = match self.timestamp {
+            Some(timestamp) => match DateTime::parse_from_rfc3339(&timestamp) {
+                Ok(time) => time.with_timezone(&Local),
+                Err(_) => {
+                    fields
+                        .entry("timestamp".to_owned())
+                        .or_insert(FieldValue::Str(timestamp));
+                    Local::now()
+                }
+            },
+            None => Local::now(),
+        };
+        Ok(CollectedEvent {
+            target: self.target,
+            level,
+            fields,
+            time,
+            // The JSON only has the names and fields of the spans.
+            spans: self
+                .spans
+                .into_iter()
+                .map(|span| SpanContext {
+                    id: 0,
+                    name: span.name,
+                    target: String::new(),
+                    level,
+                    fields: from_json_fields(span.fields),
+                })
+                .collect(),
+            file: self.filename,
+            line: self.line_number,
+            module_path: self.module_path,
+            thread: ThreadInfo {
+                name: self.thread_name,
+                id: self.thread_id.unwrap_or_default(),
+            },
+        })
+    }
+}
+
+fn format_timestamp(time: &DateTime<Local>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Micros, false)
+}
+
+fn to_json_fields(fields: &BTreeMap<String, FieldValue>) -> BTreeMap<String, Value> {
+    fields
+        .iter()
+        .map(|(key, value)| (key.clone(), to_json_value(value)))
+        .collect()
+}
+
+fn to_json_value(value: &FieldValue) -> Value {
+    match value {
+        FieldValue::I64(v) => Value::from(*v),
+        FieldValue::U64(v) => Value::from(*v),
+        FieldValue::F64(v) => {
+            Number::from_f64(*v).map_or_else(|| Value::from(v.to_string()), Value::Number)
+        }
+        FieldValue::Bool(v) => Value::from(*v),
+        FieldValue::Str(_) | FieldValue::Debug(_) | FieldValue::Error { .. } => {
+            Value::from(value.to_string())
+        }
+    }
+}
+
+fn from_json_fields(fields: BTreeMap<String, Value>) -> BTreeMap<String, FieldValue> {
+    fields
+        .into_iter()
+        .map(|(key, value)| (key, from_json_value(value)))
+        .collect()
+}
+
+fn from_json_value(value: Value) -> FieldValue {
+    match value {
+        Value::Bool(v) => FieldValue::Bool(v),
+        Value::Number(v) => {
+            if let Some(v) = v.as_u64() {
+                FieldValue::U64(v)
+            } else if let Some(v) = v.as_i64() {
+                FieldValue::I64(v)
+            } else {
+                FieldValue::F64(v.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        Value::String(v) => FieldValue::Str(v),
+        value @ (Value::Null | Value::Array(_) | Value::Object(_)) => {
+            FieldValue::Debug(value.to_string())
+        }
+    }
+}
+
+/// Parses a line of JSON written by [`write_jsonl`] or
+/// `tracing_subscriber`'s JSON formatter.
+pub fn parse_event(line: &str) -> Result<CollectedEvent, String> {
+    from_json_bytes(line.as_bytes())
+}
+
+pub(super) fn from_json_bytes(bytes: &[u8]) -> Result<CollectedEvent, String> {
+    serde_json::from_slice::<JsonEvent>(bytes)
+        .map_err(|err| err.to_string())?
+        .into_event()
+}
+
+pub(super) fn to_json_bytes(event: &CollectedEvent) -> serde_json::Result<Vec<u8>> {
+    serde_json::to_vec(&JsonEvent::from(event))
+}
+
+/// Outcome of [`EventCollector::import_jsonl`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub imported: usize,
+    /// Lines that couldn't be parsed as an event.
+    pub skipped: usize,
+}
+
+/// Writes `events` as JSON Lines, returning the number of events written.
+pub fn write_jsonl<'a>(
+    mut writer: impl Write,
+    events: impl IntoIterator<Item = &'a CollectedEvent>,
+) -> io::Result<usize> {
+    let mut count = 0;
+    for event in events {
+        serde_json::to_writer(&mut writer, &JsonEvent::from(event))?;
+        writer.write_all(b"\n")?;
+        count += 1;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+impl EventCollector {
+    /// Writes all collected events as JSON Lines, see [`write_jsonl`]. The
+    /// event store stays locked while writing.
+    pub fn export_jsonl(&self, writer: impl Write) -> io::Result<usize> {
+        let events = self.read();
+        write_jsonl(writer, events.iter().map(|(_, event)| event))
+    }
+
+    /// Adds the events of a JSON Lines log, see [`parse_event`]. Lines that
+    /// aren't events are skipped, so logs mixed with other output can be
+    /// imported too. Like [`EventCollector::import`], this bypasses the
+    /// capture config.
+    pub fn import_jsonl(&self, reader: impl BufRead) -> io::Result<ImportSummary> {
+        let mut summary = ImportSummary::default();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_event(&line) {
+                Ok(event) => {
+                    self.import(event);
+                    summary.imported += 1;
+                }
+                Err(_) => summary.skipped += 1,
+            }
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use tracing::Level;
+
+    use super::*;
+
+    fn event() -> CollectedEvent {
+        let fields = |fields: &[(&str, FieldValue)]| {
+            fields
+                .iter()
+                .map(|(key, value)| ((*key).to_owned(), value.clone()))
+                .collect()
+        };
+        CollectedEvent {
+            target: "my_app::http".to_owned(),
+            level: Level::WARN,
+            fields: fields(&[
+                (
+                    "message",
+                    FieldValue::Str("slow \"request\"\nretrying".to_owned()),
+                ),
+                ("status", FieldValue::U64(503)),
+                ("offset", FieldValue::I64(-4)),
+                ("latency", FieldValue::F64(1.5)),
+                ("cached", FieldValue::Bool(false)),
+            ]),
+            time: Local.timestamp_micros(1_714_564_800_123_456).unwrap(),
+            // Span ids and targets aren't exported, and spans take the level
+            // of their event on import.
+            spans: vec![SpanContext {
+                id: 0,
+                name: "request".to_owned(),
+                target: String::new(),
+                level: Level::WARN,
+                fields: fields(&[("path", FieldValue::Str("/users".to_owned()))]),
+            }],
+            file: Some("src/http.rs".to_owned()),
+            line: Some(42),
+            module_path: Some("my_app::http".to_owned()),
+            thread: ThreadInfo {
+                name: Some("worker-1".to_owned()),
+                id: "ThreadId(3)".to_owned(),
+            },
+        }
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let events = [event(), event()];
+        let mut jsonl = Vec::new();
+        assert_eq!(write_jsonl(&mut jsonl, &events).unwrap(), 2);
+
+        let jsonl = String::from_utf8(jsonl).unwrap();
+        assert_eq!(jsonl.lines().count(), 2);
+        for line in jsonl.lines() {
+            let parsed = parse_event(line).unwrap();
+            assert_eq!(format!("{:?}", parsed), format!("{:?}", event()));
+        }
+    }
+
+    #[test]
+    fn parses_tracing_subscriber_json() {
+        // tracing_subscriber::fmt().json().with_thread_names(true)
+        let event = parse_event(
+            r#"{"timestamp":"2024-05-01T12:00:00.123456Z","level":"INFO","fields":{"message":"hello","id":42},"target":"my_app","span":{"path":"/","name":"request"},"spans":[{"path":"/","name":"request"}],"threadName":"main"}"#,
+        )
+        .unwrap();
+        assert_eq!(event.level, Level::INFO);
+        assert_eq!(event.target, "my_app");
+        assert_eq!(
+            event.time,
+            Local.timestamp_micros(1_714_564_800_123_456).unwrap()
+        );
+        assert_eq!(event.fields.len(), 2);
+        assert_eq!(event.fields["message"], FieldValue::Str("hello".to_owned()));
+        assert_eq!(event.fields["id"], FieldValue::U64(42));
+        assert_eq!(event.spans.len(), 1);
+        assert_eq!(event.spans[0].name, "request");
+        assert_eq!(
+            event.spans[0].fields["path"],
+            FieldValue::Str("/".to_owned())
+        );
+        assert_eq!(event.thread.name.as_deref(), Some("main"));
+
+        // .flatten_event(true)
+        let event = parse_event(
+            r#"{"timestamp":"2024-05-01T12:00:00.123456Z","level":"DEBUG","message":"hello","id":-1,"target":"my_app","span":{"name":"request"},"spans":[{"name":"request"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(event.fields.len(), 2);
+        assert_eq!(event.fields["id"], FieldValue::I64(-1));
+    }
+
+    #[test]
+    fn keeps_timestamps_that_are_not_rfc3339() {
+        let before = Local::now();
+        // .with_timer(Uptime::default())
+        let event = parse_event(
+            r#"{"timestamp":"   0.001234567s","level":"INFO","fields":{"message":"up"},"target":"my_app"}"#,
+        )
+        .unwrap();
+        assert!(event.time >= before);
+        assert_eq!(
+            event.fields["timestamp"],
+            FieldValue::Str("   0.001234567s".to_owned())
+        );
+
+        // .without_time()
+        let event = parse_event(r#"{"level":"INFO","fields":{"message":"now"},"target":"my_app"}"#)
+            .unwrap();
+        assert!(event.time >= before);
+        assert!(!event.fields.contains_key("timestamp"));
+    }
+
+    #[test]
+    fn import_skips_other_lines() {
+        let collector = EventCollector::new();
+        let jsonl = concat!(
+            r#"{"level":"INFO","fields":{"message":"one"},"target":"my_app"}"#,
+            "\n\nnot json\n",
+            r#"{"level":"LOUD","fields":{"message":"two"},"target":"my_app"}"#,
+            "\n",
+            r#"{"level":"WARN","fields":{"message":"three"},"target":"my_app"}"#,
+        );
+        let summary = collector.import_jsonl(jsonl.as_bytes()).unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                imported: 2,
+                skipped: 2
+            }
+        );
+        assert_eq!(collector.read().len(), 2);
+    }
+}
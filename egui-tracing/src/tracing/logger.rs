@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+
+use chrono::Local;
+use log::kv::{self, Key, Value, VisitSource, VisitValue};
+use log::{Log, Metadata, Record, SetLoggerError};
+
+use super::collector::EventCollector;
+use super::event::{CollectedEvent, ThreadInfo};
+use super::field::FieldValue;
+
+/// A [`log::Log`] feeding records straight into an [`EventCollector`],
+/// without bridging them through `tracing`.
+#[derive(Debug, Clone)]
+pub struct CollectorLogger {
+    collector: EventCollector,
+}
+
+impl EventCollector {
+    /// Returns a logger collecting `log` records into this collector. Key-value
+    /// pairs of the records become fields of the collected events.
+    pub fn log_logger(&self) -> CollectorLogger {
+        CollectorLogger {
+            collector: self.clone(),
+        }
+    }
+
+    /// Installs [`EventCollector::log_logger`] as the global logger.
+    ///
+    /// The maximum level of `log` is set to trace, so the records are filtered
+    /// by the collector's capture config even if it changes later.
+    pub fn init_log_logger(&self) -> Result<(), SetLoggerError> {
+        log::set_boxed_logger(Box::new(self.log_logger()))?;
+        log::set_max_level(log::LevelFilter::Trace);
+        Ok(())
+    }
+}
+
+impl Log for CollectorLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.collector
+            .should_collect(&to_tracing_level(metadata.level()), metadata.target())
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let mut fields = BTreeMap::new();
+        fields.insert(
+            "message".to_owned(),
+            FieldValue::Debug(record.args().to_string()),
+        );
+        // Visiting only fails if the visitor does.
+        let _ = record.key_values().visit(&mut KeyValueVisitor(&mut fields));
+
+        self.collector.collect(CollectedEvent {
+            target: record.target().to_owned(),
+            level: to_tracing_level(record.level()),
+            fields,
+            time: Local::now(),
+            spans: Vec::new(),
+            file: record.file().map(str::to_owned),
+            line: record.line(),
+            module_path: record.module_path().map(str::to_owned),
+            thread: ThreadInfo::current(),
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+fn to_tracing_level(level: log::Level) -> tracing::Level {
+    match level {
+        log::Level::Error => tracing::Level::ERROR,
+        log::Level::Warn => tracing::Level::WARN,
+        log::Level::Info => tracing::Level::INFO,
+        log::Level::Debug => tracing::Level::DEBUG,
+        log::Level::Trace => tracing::Level::TRACE,
+    }
+}
+
+struct KeyValueVisitor<'a>(&'a mut BTreeMap<String, FieldValue>);
+
+impl<'a, 'kvs> VisitSource<'kvs> for KeyValueVisitor<'a> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let mut field = None;
+        value.visit(ValueVisitor(&mut field))?;
+        if let Some(field) = field {
+            self.0.insert(key.as_str().to_owned(), field);
+        }
+        Ok(())
+    }
+}
+
+struct ValueVisitor<'a>(&'a mut Option<FieldValue>);
+
+impl<'a, 'v> VisitValue<'v> for ValueVisitor<'a> {
+    fn visit_any(&mut self, value: Value<'_>) -> Result<(), kv::Error> {
+        *self.0 = Some(FieldValue::Debug(value.to_string()));
+        Ok(())
+    }
+
+    fn visit_u64(&mut self, value: u64) -> Result<(), kv::Error> {
+        *self.0 = Some(FieldValue::U64(value));
+        Ok(())
+    }
+
+    fn visit_i64(&mut self, value: i64) -> Result<(), kv::Error> {
+        *self.0 = Some(FieldValue::I64(value));
+        Ok(())
+    }
+
+    fn visit_f64(&mut self, value: f64) -> Result<(), kv::Error> {
+        *self.0 = Some(FieldValue::F64(value));
+        Ok(())
+    }
+
+    fn visit_bool(&mut self, value: bool) -> Result<(), kv::Error> {
+        *self.0 = Some(FieldValue::Bool(value));
+        Ok(())
+    }
+
+    fn visit_str(&mut self, value: &str) -> Result<(), kv::Error> {
+        *self.0 = Some(FieldValue::Str(value.to_owned()));
+        Ok(())
+    }
+}
@@ -1,5 +1,22 @@
 pub mod collector;
+pub mod directive;
 pub mod event;
+pub mod field;
+pub mod json;
+#[cfg(feature = "log")]
+pub mod logger;
+pub mod remote;
+mod repaint;
+pub mod span;
+mod store;
 
-pub use collector::EventCollector;
-pub use event::CollectedEvent;
+pub use collector::{CaptureFilter, EventCollector};
+pub use directive::{DirectiveError, Directives};
+pub use event::{CollectedEvent, SpanContext, ThreadInfo};
+pub use field::FieldValue;
+pub use json::ImportSummary;
+#[cfg(feature = "log")]
+pub use logger::CollectorLogger;
+pub use remote::{OverflowPolicy, RemoteAddr, RemoteReceiver, RemoteSender};
+pub use span::CollectedSpan;
+pub use store::{EventsGuard, SpansGuard, StoreGuard};
@@ -0,0 +1,750 @@
+//! Streams events from one process to an [`EventCollector`] in another, over
+//! TCP or a Unix domain socket.
+//!
+//! A [`RemoteSender`] connects to a [`RemoteReceiver`] and writes a header of
+//! the magic bytes `EGTR` and a version byte, followed by frames of a kind
+//! byte, a big-endian `u32` payload length and the payload:
+//!
+//! - `0`: an event, as JSON in the format of [`super::json`].
+//! - `1`: a big-endian `u64` count of events the sender dropped.
+//!
+//! Receivers skip frames of unknown kinds.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use super::collector::{build_event, insert_span_fields, record_span_fields, EventCollector};
+use super::event::CollectedEvent;
+use super::field::FieldVisitor;
+use super::json;
+
+const MAGIC: &[u8; 4] = b"EGTR";
+const VERSION: u8 = 1;
+
+const FRAME_EVENT: u8 = 0;
+const FRAME_DROPPED: u8 = 1;
+const MAX_FRAME_LEN: u32 = 16 * 1024 * 1024;
+/// Capacity a frame buffer shrinks back to after a larger frame.
+const FRAME_BUFFER_LEN: usize = 64 * 1024;
+const MAX_CONNECTIONS: usize = 64;
+
+const MIN_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteAddr {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl RemoteAddr {
+    fn connect(&self) -> io::Result<Stream> {
+        match self {
+            Self::Tcp(addr) => {
+                let stream = TcpStream::connect(addr)?;
+                stream.set_nodelay(true)?;
+                Ok(Stream::Tcp(stream))
+            }
+            #[cfg(unix)]
+            Self::Unix(path) => UnixStream::connect(path).map(Stream::Unix),
+        }
+    }
+}
+
+impl From<SocketAddr> for RemoteAddr {
+    fn from(addr: SocketAddr) -> Self {
+        Self::Tcp(addr)
+    }
+}
+
+impl Display for RemoteAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{}", addr),
+            #[cfg(unix)]
+            Self::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Self::Tcp(stream) => stream.try_clone().map(Self::Tcp),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.try_clone().map(Self::Unix),
+        }
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.shutdown(Shutdown::Both),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.shutdown(Shutdown::Both),
+        }
+    }
+
+    /// Accepted streams inherit the listener's non-blocking mode on some
+    /// platforms.
+    fn set_blocking(&self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.set_nonblocking(false),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.set_nonblocking(false),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Self::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// What to do with an event when the queue of a [`RemoteSender`] is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Discards the new event.
+    DropNewest,
+    /// Discards the oldest queued event.
+    DropOldest,
+    /// Waits for the queue to make room.
+    #[default]
+    Block,
+}
+
+/// Events waiting to be sent. Fills up while the receiver is slow or
+/// disconnected.
+#[derive(Debug)]
+struct Queue {
+    events: Mutex<VecDeque<CollectedEvent>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+    /// Events dropped by the overflow policy that weren't reported yet.
+    dropped: AtomicU64,
+}
+
+impl Queue {
+    fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            events: Mutex::new(VecDeque::new()),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            capacity: capacity.max(1),
+            policy,
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn push(&self, event: CollectedEvent) {
+        let mut events = self.events.lock().unwrap();
+        if events.len() >= self.capacity {
+            match self.policy {
+                OverflowPolicy::DropNewest => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                OverflowPolicy::DropOldest => {
+                    events.pop_front();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+                OverflowPolicy::Block => {
+                    events = self
+                        .not_full
+                        .wait_while(events, |events| events.len() >= self.capacity)
+                        .unwrap();
+                }
+            }
+        }
+        events.push_back(event);
+        self.not_empty.notify_one();
+    }
+
+    fn pop(&self, timeout: Duration) -> Option<CollectedEvent> {
+        let events = self.events.lock().unwrap();
+        let (mut events, _) = self
+            .not_empty
+            .wait_timeout_while(events, timeout, |events| events.is_empty())
+            .unwrap();
+        let event = events.pop_front();
+        if event.is_some() {
+            self.not_full.notify_one();
+        }
+        event
+    }
+}
+
+/// A [`Layer`] sending events to a [`RemoteReceiver`], e.g. to watch the logs
+/// of a headless service in a separate egui application.
+///
+/// Events are queued and written by a background thread, which connects on
+/// the first event and reconnects with a backoff whenever the connection is
+/// lost. Events written right before the connection is lost may be lost with
+/// it. The layer doesn't filter events; use [`Layer::with_filter`] for that.
+#[derive(Debug, Clone)]
+pub struct RemoteSender {
+    addr: RemoteAddr,
+    capacity: usize,
+    policy: OverflowPolicy,
+    queue: Arc<OnceLock<Arc<Queue>>>,
+}
+
+impl RemoteSender {
+    pub fn new(addr: impl Into<RemoteAddr>) -> Self {
+        Self {
+            addr: addr.into(),
+            capacity: 4096,
+            policy: OverflowPolicy::DropOldest,
+            queue: Arc::new(OnceLock::new()),
+        }
+    }
+
+    /// Sends events to a Unix domain socket.
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self::new(RemoteAddr::Unix(path.into()))
+    }
+
+    /// Queues at most `capacity` events while the receiver is slow or
+    /// disconnected.
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        Self { capacity, ..self }
+    }
+
+    /// What to do with events when the queue is full. Defaults to
+    /// [`OverflowPolicy::DropOldest`]; with [`OverflowPolicy::Block`], logging
+    /// stalls while no receiver is connected.
+    pub fn with_overflow_policy(self, policy: OverflowPolicy) -> Self {
+        Self { policy, ..self }
+    }
+
+    fn send(&self, event: CollectedEvent) {
+        let queue = self.queue.get_or_init(|| {
+            let queue = Arc::new(Queue::new(self.capacity, self.policy));
+            let addr = self.addr.clone();
+            let writer_queue = queue.clone();
+            thread::Builder::new()
+                .name("egui_tracing remote sender".to_owned())
+                .spawn(move || run_sender(&addr, &writer_queue))
+                .expect("failed to spawn the remote sender thread");
+            queue
+        });
+        queue.push(event);
+    }
+}
+
+impl<S> Layer<S> for RemoteSender
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Default::default();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        insert_span_fields(&span, fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            record_span_fields(&span, values);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        if let Some(event) = build_event(event, &ctx, |_| true) {
+            self.send(event);
+        }
+    }
+}
+
+/// Whether all senders are gone, so the thread owns the last reference.
+fn is_abandoned(queue: &Arc<Queue>) -> bool {
+    Arc::strong_count(queue) == 1
+}
+
+fn run_sender(addr: &RemoteAddr, queue: &Arc<Queue>) {
+    let mut backoff = MIN_BACKOFF;
+    // An event that couldn't be written before the connection was lost.
+    let mut pending = None;
+    loop {
+        let stream = match addr.connect() {
+            Ok(stream) => stream,
+            Err(_) => {
+                if is_abandoned(queue) {
+                    return;
+                }
+                thread::sleep(backoff);
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+                continue;
+            }
+        };
+        backoff = MIN_BACKOFF;
+        if write_events(&mut BufWriter::new(stream), queue, &mut pending).is_ok() {
+            return;
+        }
+    }
+}
+
+/// Writes events until all senders are gone or the connection fails.
+fn write_events(
+    writer: &mut impl Write,
+    queue: &Arc<Queue>,
+    pending: &mut Option<CollectedEvent>,
+) -> io::Result<()> {
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    loop {
+        let dropped = queue.dropped.swap(0, Ordering::Relaxed);
+        if dropped > 0 {
+            if let Err(err) = write_frame(writer, FRAME_DROPPED, &dropped.to_be_bytes()) {
+                queue.dropped.fetch_add(dropped, Ordering::Relaxed);
+                return Err(err);
+            }
+        }
+
+        let event = match pending.take().or_else(|| queue.pop(Duration::ZERO)) {
+            Some(event) => event,
+            None => {
+                // Nothing else to batch with, so send what was written.
+                writer.flush()?;
+                match queue.pop(POLL_INTERVAL) {
+                    Some(event) => event,
+                    None if is_abandoned(queue) => return Ok(()),
+                    None => continue,
+                }
+            }
+        };
+        // Events that can't be serialized are skipped.
+        let Ok(payload) = json::to_json_bytes(&event) else {
+            continue;
+        };
+        if let Err(err) = write_frame(writer, FRAME_EVENT, &payload) {
+            *pending = Some(event);
+            return Err(err);
+        }
+    }
+}
+
+fn write_frame(writer: &mut impl Write, kind: u8, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len())
+        .ok()
+        .filter(|len| *len <= MAX_FRAME_LEN)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "frame too large"))?;
+    writer.write_all(&[kind])?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(payload)
+}
+
+#[derive(Debug)]
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    fn accept(&self) -> io::Result<Stream> {
+        match self {
+            Self::Tcp(listener) => listener.accept().map(|(stream, _)| Stream::Tcp(stream)),
+            #[cfg(unix)]
+            Self::Unix(listener) => listener.accept().map(|(stream, _)| Stream::Unix(stream)),
+        }
+    }
+}
+
+/// Accepts connections from [`RemoteSender`]s on a background thread and
+/// adds their events to an [`EventCollector`]. Stops listening and closes the
+/// connections when dropped.
+///
+/// Connections are neither authenticated nor encrypted, so only bind to
+/// interfaces that are reachable by trusted processes alone, such as
+/// `127.0.0.1` or a Unix socket with restricted permissions. At most 64
+/// connections are served at a time; further ones are closed right away.
+#[derive(Debug)]
+pub struct RemoteReceiver {
+    addr: RemoteAddr,
+    stop: Arc<AtomicBool>,
+    connections: Arc<Mutex<HashMap<u64, Stream>>>,
+}
+
+impl RemoteReceiver {
+    /// Listens on `addr`. A Unix socket is created at the path, which must
+    /// not exist yet, and removed when the receiver is dropped.
+    pub fn bind(addr: impl Into<RemoteAddr>, collector: EventCollector) -> io::Result<Self> {
+        let (listener, addr) = match addr.into() {
+            RemoteAddr::Tcp(addr) => {
+                let listener = TcpListener::bind(addr)?;
+                listener.set_nonblocking(true)?;
+                let addr = RemoteAddr::Tcp(listener.local_addr()?);
+                (Listener::Tcp(listener), addr)
+            }
+            #[cfg(unix)]
+            RemoteAddr::Unix(path) => {
+                let listener = UnixListener::bind(&path)?;
+                listener.set_nonblocking(true)?;
+                (Listener::Unix(listener), RemoteAddr::Unix(path))
+            }
+        };
+
+        let receiver = Self {
+            addr,
+            stop: Arc::new(AtomicBool::new(false)),
+            connections: Arc::new(Mutex::new(HashMap::new())),
+        };
+        let stop = receiver.stop.clone();
+        let connections = receiver.connections.clone();
+        thread::Builder::new()
+            .name("egui_tracing remote receiver".to_owned())
+            .spawn(move || accept(&listener, &collector, &stop, &connections))?;
+        Ok(receiver)
+    }
+
+    /// Listens on a Unix domain socket, see [`RemoteReceiver::bind`].
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>, collector: EventCollector) -> io::Result<Self> {
+        Self::bind(RemoteAddr::Unix(path.into()), collector)
+    }
+
+    /// The address listened on, with the actual port if port 0 was given.
+    pub fn local_addr(&self) -> &RemoteAddr {
+        &self.addr
+    }
+}
+
+impl Drop for RemoteReceiver {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        for stream in self.connections.lock().unwrap().values() {
+            let _ = stream.shutdown();
+        }
+        #[cfg(unix)]
+        if let RemoteAddr::Unix(path) = &self.addr {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn accept(
+    listener: &Listener,
+    collector: &EventCollector,
+    stop: &Arc<AtomicBool>,
+    connections: &Arc<Mutex<HashMap<u64, Stream>>>,
+) {
+    let mut next_id = 0;
+    while !stop.load(Ordering::Relaxed) {
+        let stream = match listener.accept() {
+            Ok(stream) => stream,
+            // Polls, as there's no portable way to interrupt `accept`.
+            Err(_) => {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+        };
+        // Senders retry with a backoff until a connection is closed.
+        if connections.lock().unwrap().len() >= MAX_CONNECTIONS {
+            continue;
+        }
+        let Ok(handle) = stream.set_blocking().and_then(|()| stream.try_clone()) else {
+            continue;
+        };
+
+        let id = next_id;
+        next_id += 1;
+        connections.lock().unwrap().insert(id, handle);
+        // The receiver may have been dropped before the connection was added.
+        if stop.load(Ordering::Relaxed) {
+            let _ = stream.shutdown();
+        }
+
+        let collector = collector.clone();
+        let connections = connections.clone();
+        let _ = thread::Builder::new()
+            .name("egui_tracing remote connection".to_owned())
+            .spawn(move || {
+                // The connection is closed on errors, the sender reconnects.
+                let _ = read_events(stream, &collector);
+                connections.lock().unwrap().remove(&id);
+            });
+    }
+}
+
+fn read_events(stream: impl Read, collector: &EventCollector) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut header = [0; 5];
+    reader.read_exact(&mut header)?;
+    if &header[..4] != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an egui_tracing event stream",
+        ));
+    }
+    if header[4] != VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported event stream version {}", header[4]),
+        ));
+    }
+
+    let mut payload = Vec::new();
+    loop {
+        let mut frame = [0; 5];
+        match reader.read_exact(&mut frame) {
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            result => result?,
+        }
+        let len = u32::from_be_bytes([frame[1], frame[2], frame[3], frame[4]]);
+        if len > MAX_FRAME_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frame too large",
+            ));
+        }
+        // The buffer only grows as the payload arrives, so a length that
+        // isn't followed by as much data doesn't allocate it all.
+        payload.clear();
+        if payload.capacity() > FRAME_BUFFER_LEN {
+            payload.shrink_to(FRAME_BUFFER_LEN);
+        }
+        (&mut reader)
+            .take(u64::from(len))
+            .read_to_end(&mut payload)?;
+        if payload.len() < len as usize {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        match frame[0] {
+            FRAME_EVENT => match json::from_json_bytes(&payload) {
+                Ok(event) => collector.import(event),
+                Err(_) => collector.add_dropped(1),
+            },
+            FRAME_DROPPED => {
+                if let Ok(count) = <[u8; 8]>::try_from(payload.as_slice()) {
+                    collector.add_dropped(u64::from_be_bytes(count));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use tracing::Dispatch;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+    use crate::tracing::FieldValue;
+
+    fn stream(frames: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        for (kind, payload) in frames {
+            write_frame(&mut bytes, *kind, payload).unwrap();
+        }
+        bytes
+    }
+
+    fn has_message(collector: &EventCollector, message: &str) -> bool {
+        collector
+            .read()
+            .iter()
+            .any(|(_, event)| event.fields["message"].to_string() == message)
+    }
+
+    fn events(messages: &[&str]) -> Vec<CollectedEvent> {
+        let collector = EventCollector::new();
+        let dispatch = Dispatch::new(tracing_subscriber::registry().with(collector.clone()));
+        tracing::dispatcher::with_default(&dispatch, || {
+            for message in messages {
+                tracing::info!("{}", message);
+            }
+        });
+        collector.events()
+    }
+
+    fn message(event: CollectedEvent) -> String {
+        event.fields["message"].to_string()
+    }
+
+    fn drain(queue: &Queue) -> Vec<String> {
+        std::iter::from_fn(|| queue.pop(Duration::ZERO))
+            .map(message)
+            .collect()
+    }
+
+    #[test]
+    fn full_queue_drops_by_policy() {
+        for (policy, kept) in [
+            (OverflowPolicy::DropNewest, ["a", "b"]),
+            (OverflowPolicy::DropOldest, ["b", "c"]),
+        ] {
+            let queue = Queue::new(2, policy);
+            for event in events(&["a", "b", "c"]) {
+                queue.push(event);
+            }
+            assert_eq!(drain(&queue), kept, "{:?}", policy);
+            assert_eq!(queue.dropped.load(Ordering::Relaxed), 1, "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn full_queue_blocks_until_an_event_is_taken() {
+        let queue = Arc::new(Queue::new(1, OverflowPolicy::Block));
+        let mut events = events(&["a", "b"]).into_iter();
+        queue.push(events.next().unwrap());
+        let pusher = {
+            let queue = queue.clone();
+            let event = events.next().unwrap();
+            thread::spawn(move || queue.push(event))
+        };
+
+        thread::sleep(Duration::from_millis(50));
+        assert!(!pusher.is_finished());
+        assert_eq!(queue.pop(Duration::ZERO).map(message).as_deref(), Some("a"));
+        pusher.join().unwrap();
+        assert_eq!(drain(&queue), ["b"]);
+        assert_eq!(queue.dropped.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let collector = EventCollector::new();
+        let dispatch = Dispatch::new(tracing_subscriber::registry().with(collector.clone()));
+        tracing::dispatcher::with_default(&dispatch, || tracing::warn!(status = 503, "hello"));
+        let event = collector.events().remove(0);
+        let payload = json::to_json_bytes(&event).unwrap();
+
+        let remote = EventCollector::new();
+        let bytes = stream(&[
+            (FRAME_EVENT, &payload),
+            (FRAME_DROPPED, &3u64.to_be_bytes()),
+            (42, b"unknown kinds are skipped"),
+            (FRAME_EVENT, b"not json"),
+        ]);
+        read_events(bytes.as_slice(), &remote).unwrap();
+
+        let events = remote.events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].level, tracing::Level::WARN);
+        assert_eq!(events[0].target, event.target);
+        assert_eq!(events[0].fields["status"], FieldValue::U64(503));
+        assert!(has_message(&remote, "hello"));
+        // The dropped events and the one that couldn't be parsed.
+        assert_eq!(remote.dropped_events(), 4);
+    }
+
+    #[test]
+    fn rejects_invalid_streams() {
+        let collector = EventCollector::new();
+        let error = |bytes: &[u8]| read_events(bytes, &collector).unwrap_err().kind();
+
+        assert_eq!(error(b"HTTP/1.1"), io::ErrorKind::InvalidData);
+        assert_eq!(error(b"EGTR\x02"), io::ErrorKind::InvalidData);
+
+        let mut too_large = stream(&[]);
+        too_large.push(FRAME_EVENT);
+        too_large.extend((MAX_FRAME_LEN + 1).to_be_bytes());
+        assert_eq!(error(&too_large), io::ErrorKind::InvalidData);
+
+        // A length without the payload fails without allocating it.
+        let mut truncated = stream(&[]);
+        truncated.push(FRAME_EVENT);
+        truncated.extend(MAX_FRAME_LEN.to_be_bytes());
+        truncated.extend(b"{}");
+        assert_eq!(error(&truncated), io::ErrorKind::UnexpectedEof);
+
+        assert!(write_frame(
+            &mut Vec::new(),
+            FRAME_EVENT,
+            &vec![0; MAX_FRAME_LEN as usize + 1]
+        )
+        .is_err());
+    }
+
+    /// Logs `message` until it arrives at `collector`.
+    fn log_until_received(dispatch: &Dispatch, collector: &EventCollector, message: &str) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !has_message(collector, message) {
+            assert!(Instant::now() < deadline, "`{}` wasn't received", message);
+            tracing::dispatcher::with_default(dispatch, || tracing::info!("{}", message));
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    #[test]
+    fn reconnects_over_loopback() {
+        let first = EventCollector::new();
+        let receiver =
+            RemoteReceiver::bind(SocketAddr::from(([127, 0, 0, 1], 0)), first.clone()).unwrap();
+        let addr = receiver.local_addr().clone();
+        let dispatch =
+            Dispatch::new(tracing_subscriber::registry().with(RemoteSender::new(addr.clone())));
+        log_until_received(&dispatch, &first, "first");
+
+        drop(receiver);
+        let second = EventCollector::new();
+        // The port is free once the accepting thread noticed the drop.
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let _receiver = loop {
+            match RemoteReceiver::bind(addr.clone(), second.clone()) {
+                Ok(receiver) => break receiver,
+                Err(err) => assert!(Instant::now() < deadline, "{}", err),
+            }
+            thread::sleep(Duration::from_millis(50));
+        };
+        log_until_received(&dispatch, &second, "second");
+    }
+}
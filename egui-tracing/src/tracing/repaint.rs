@@ -0,0 +1,173 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+#[cfg(target_arch = "wasm32")]
+use chrono::{DateTime, Local};
+
+/// Requests repaints of an [`egui::Context`] when events arrive, at most one
+/// per interval.
+///
+/// egui logs while holding the context's lock, and so may the integration's
+/// repaint callback, so requesting a repaint on the logging thread could
+/// deadlock. The repaints are requested from a thread of their own instead,
+/// or from a task on wasm32.
+#[derive(Debug)]
+pub(super) struct Repainter {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    /// Set by events, so that only the first event since the last repaint
+    /// has to wake the thread or spawn a task.
+    pending: AtomicBool,
+    state: Mutex<State>,
+    wake: Condvar,
+}
+
+#[derive(Debug)]
+struct State {
+    ctx: Option<egui::Context>,
+    interval: Duration,
+    #[cfg(not(target_arch = "wasm32"))]
+    started: bool,
+    closed: bool,
+    #[cfg(target_arch = "wasm32")]
+    throttle: Throttle,
+}
+
+impl Default for Repainter {
+    fn default() -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                pending: AtomicBool::new(false),
+                state: Mutex::new(State {
+                    ctx: None,
+                    interval: Duration::from_millis(100),
+                    #[cfg(not(target_arch = "wasm32"))]
+                    started: false,
+                    closed: false,
+                    #[cfg(target_arch = "wasm32")]
+                    throttle: Throttle::default(),
+                }),
+                wake: Condvar::new(),
+            }),
+        }
+    }
+}
+
+impl Repainter {
+    pub(super) fn set_context(&self, ctx: egui::Context) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.ctx = Some(ctx);
+        #[cfg(not(target_arch = "wasm32"))]
+        if !std::mem::replace(&mut state.started, true) {
+            let shared = self.shared.clone();
+            std::thread::Builder::new()
+                .name("egui-tracing-repaint".to_owned())
+                .spawn(move || run(&shared))
+                .expect("failed to spawn the repaint thread");
+        }
+    }
+
+    pub(super) fn set_interval(&self, interval: Duration) {
+        self.shared.state.lock().unwrap().interval = interval;
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(super) fn request(&self) {
+        if !self.shared.pending.swap(true, Ordering::AcqRel) {
+            // Locking orders this after the thread's check of `pending`.
+            let _state = self.shared.state.lock().unwrap();
+            self.shared.wake.notify_one();
+        }
+    }
+
+    /// Without threads the repaint is requested from a task of its own,
+    /// which runs once the code that logged has returned.
+    #[cfg(target_arch = "wasm32")]
+    pub(super) fn request(&self) {
+        if self.shared.pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let shared = self.shared.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            shared.pending.store(false, Ordering::Release);
+            let mut state = shared.state.lock().unwrap();
+            let interval = state.interval;
+            if let Some(ctx) = state.ctx.clone().filter(|_| !state.closed) {
+                state.throttle.request(&ctx, interval);
+            }
+        });
+    }
+}
+
+impl Drop for Repainter {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.wake.notify_one();
+    }
+}
+
+/// Requests a repaint whenever `pending` is set, then waits for the interval
+/// to pass, until the [`Repainter`] is dropped.
+#[cfg(not(target_arch = "wasm32"))]
+fn run(shared: &Shared) {
+    let mut state = shared.state.lock().unwrap();
+    loop {
+        state = shared
+            .wake
+            .wait_while(state, |state| {
+                !state.closed && !shared.pending.load(Ordering::Acquire)
+            })
+            .unwrap();
+        if state.closed {
+            return;
+        }
+        shared.pending.store(false, Ordering::Release);
+        let ctx = state.ctx.clone();
+        let interval = state.interval;
+        // The repaint may log, which must not wait for this lock.
+        drop(state);
+        if let Some(ctx) = ctx {
+            ctx.request_repaint();
+        }
+        state = shared.state.lock().unwrap();
+        state = shared
+            .wake
+            .wait_timeout_while(state, interval, |state| !state.closed)
+            .unwrap()
+            .0;
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug, Default)]
+struct Throttle {
+    /// Earliest time the next immediate repaint may be requested.
+    next: Option<DateTime<Local>>,
+    /// Time a delayed repaint has already been requested for.
+    scheduled: Option<DateTime<Local>>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Throttle {
+    fn request(&mut self, ctx: &egui::Context, interval: Duration) {
+        let now = Local::now();
+        match self.next {
+            Some(next) if next > now => {
+                if self.scheduled != Some(next) {
+                    ctx.request_repaint_after((next - now).to_std().unwrap_or_default());
+                    self.scheduled = Some(next);
+                }
+            }
+            _ => {
+                ctx.request_repaint();
+                self.next = chrono::Duration::from_std(interval)
+                    .ok()
+                    .map(|interval| now + interval);
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+
+use super::event::ThreadInfo;
+use super::field::{fields_size, FieldValue};
+use super::store::ApproxSize;
+
+/// Lifecycle of a span, from its creation until it is closed.
+#[derive(Debug, Clone)]
+pub struct CollectedSpan {
+    /// The span's [`tracing::span::Id`], which may be reused once the span is closed.
+    pub id: u64,
+    /// Sequence number of the parent span in the collector's span store.
+    pub parent: Option<u64>,
+    pub name: String,
+    pub target: String,
+    pub level: tracing::Level,
+    pub fields: BTreeMap<String, FieldValue>,
+    pub opened: DateTime<Local>,
+    pub closed: Option<DateTime<Local>>,
+    /// Time spent inside the span.
+    pub busy: Duration,
+    /// Time the span was open but not entered.
+    pub idle: Duration,
+    /// Thread the span was first entered on.
+    pub thread: Option<ThreadInfo>,
+}
+
+impl CollectedSpan {
+    /// Time between opening and closing the span, or until now while it is
+    /// still open.
+    pub fn duration(&self) -> Duration {
+        self.closed
+            .unwrap_or_else(Local::now)
+            .signed_duration_since(self.opened)
+            .to_std()
+            .unwrap_or_default()
+    }
+}
+
+impl ApproxSize for CollectedSpan {
+    fn approx_size(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + self.name.len()
+            + self.target.len()
+            + fields_size(&self.fields)
+            + self.thread.as_ref().map_or(0, ThreadInfo::approx_size)
+    }
+}
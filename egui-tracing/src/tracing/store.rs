@@ -0,0 +1,315 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, MutexGuard};
+
+use super::event::CollectedEvent;
+use super::span::CollectedSpan;
+
+pub trait ApproxSize {
+    fn approx_size(&self) -> usize;
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Retention {
+    pub max_len: Option<usize>,
+    pub max_bytes: Option<usize>,
+}
+
+/// Items are numbered with a sequence number that keeps increasing across
+/// evictions and clears, so a reader can remember how far it has read.
+///
+/// Items are shared, so a reader can keep the ones it needs without holding
+/// the lock.
+#[derive(Debug)]
+pub struct Store<T> {
+    items: VecDeque<Arc<T>>,
+    first_seq: u64,
+    bytes: usize,
+    /// Items evicted to keep within the retention limits.
+    evicted: u64,
+    /// Items that never made it into the store.
+    dropped: u64,
+}
+
+impl<T> Default for Store<T> {
+    fn default() -> Self {
+        Self {
+            items: VecDeque::new(),
+            first_seq: 0,
+            bytes: 0,
+            evicted: 0,
+            dropped: 0,
+        }
+    }
+}
+
+impl<T: ApproxSize> Store<T> {
+    pub fn push(&mut self, item: T, retention: &Retention) -> u64 {
+        let seq = self.next_seq();
+        self.bytes += item.approx_size();
+        self.items.push_back(Arc::new(item));
+        self.evict(retention);
+        seq
+    }
+
+    pub fn evict(&mut self, retention: &Retention) {
+        while self.exceeds(retention) {
+            match self.items.pop_front() {
+                Some(item) => {
+                    self.bytes -= item.approx_size();
+                    self.first_seq += 1;
+                    self.evicted += 1;
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn exceeds(&self, retention: &Retention) -> bool {
+        retention.max_len.is_some_and(|max| self.items.len() > max)
+            || retention.max_bytes.is_some_and(|max| self.bytes > max)
+    }
+
+    /// Updates the item in place, keeping track of its changed size. Readers
+    /// still sharing the item keep the old version.
+    pub fn update(&mut self, seq: u64, f: impl FnOnce(&mut T))
+    where
+        T: Clone,
+    {
+        let Some(index) = self.index(seq) else {
+            return;
+        };
+        let item = Arc::make_mut(&mut self.items[index]);
+        self.bytes -= item.approx_size();
+        f(item);
+        self.bytes += item.approx_size();
+    }
+}
+
+impl<T> Store<T> {
+    fn index(&self, seq: u64) -> Option<usize> {
+        let index = usize::try_from(seq.checked_sub(self.first_seq)?).ok()?;
+        (index < self.items.len()).then_some(index)
+    }
+
+    pub fn first_seq(&self) -> u64 {
+        self.first_seq
+    }
+
+    pub fn add_dropped(&mut self, dropped: u64) {
+        self.dropped += dropped;
+    }
+
+    pub fn next_seq(&self) -> u64 {
+        self.first_seq + self.items.len() as u64
+    }
+
+    pub fn get(&self, seq: u64) -> Option<&Arc<T>> {
+        self.items.get(self.index(seq)?)
+    }
+
+    pub fn iter_since(&self, cursor: u64) -> impl Iterator<Item = (u64, &Arc<T>)> {
+        let start = cursor.max(self.first_seq);
+        let skip = usize::try_from(start - self.first_seq)
+            .map_or(self.items.len(), |skip| skip.min(self.items.len()));
+        (start..).zip(self.items.range(skip..))
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn evicted(&self) -> u64 {
+        self.evicted
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    pub fn clear(&mut self) {
+        self.first_seq = self.next_seq();
+        self.items = VecDeque::new();
+        self.bytes = 0;
+        self.evicted = 0;
+        self.dropped = 0;
+    }
+}
+
+/// Read access to the items stored by an [`EventCollector`](super::EventCollector).
+///
+/// The collector can't record new items while this guard is alive, so it
+/// should be dropped as soon as possible.
+pub struct StoreGuard<'a, T>(pub(super) MutexGuard<'a, Store<T>>);
+
+pub type EventsGuard<'a> = StoreGuard<'a, CollectedEvent>;
+pub type SpansGuard<'a> = StoreGuard<'a, CollectedSpan>;
+
+impl<'a, T> StoreGuard<'a, T> {
+    /// Sequence number of the oldest item still stored.
+    pub fn first_seq(&self) -> u64 {
+        self.0.first_seq()
+    }
+
+    /// Sequence number the next collected item will get.
+    pub fn next_seq(&self) -> u64 {
+        self.0.next_seq()
+    }
+
+    pub fn get(&self, seq: u64) -> Option<&T> {
+        self.0.get(seq).map(Arc::as_ref)
+    }
+
+    /// Iterates over the stored items with a sequence number of at least `cursor`.
+    pub fn iter_since(&self, cursor: u64) -> impl Iterator<Item = (u64, &T)> {
+        self.0
+            .iter_since(cursor)
+            .map(|(seq, item)| (seq, item.as_ref()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, &T)> {
+        self.iter_since(0)
+    }
+
+    /// Like [`StoreGuard::get`], but the item can be kept after the guard is
+    /// dropped.
+    pub fn get_shared(&self, seq: u64) -> Option<Arc<T>> {
+        self.0.get(seq).cloned()
+    }
+
+    /// Like [`StoreGuard::iter_since`], but the items can be kept after the
+    /// guard is dropped.
+    pub fn iter_shared_since(&self, cursor: u64) -> impl Iterator<Item = (u64, Arc<T>)> + '_ {
+        self.0
+            .iter_since(cursor)
+            .map(|(seq, item)| (seq, item.clone()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.len() == 0
+    }
+
+    /// Number of items evicted by the retention limits since the store was
+    /// last cleared.
+    pub fn evicted(&self) -> u64 {
+        self.0.evicted()
+    }
+
+    /// Number of items dropped before they were stored, since the store was
+    /// last cleared.
+    pub fn dropped(&self) -> u64 {
+        self.0.dropped()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An item whose size is given by its value.
+    #[derive(Debug, Clone, PartialEq)]
+    struct Item(usize);
+
+    impl ApproxSize for Item {
+        fn approx_size(&self) -> usize {
+            self.0
+        }
+    }
+
+    fn values(store: &Store<Item>, cursor: u64) -> Vec<(u64, usize)> {
+        store
+            .iter_since(cursor)
+            .map(|(seq, item)| (seq, item.0))
+            .collect()
+    }
+
+    #[test]
+    fn evicts_oldest_beyond_max_len() {
+        let retention = Retention {
+            max_len: Some(2),
+            max_bytes: None,
+        };
+        let mut store = Store::default();
+        for value in 0..5 {
+            assert_eq!(store.push(Item(value), &retention), value as u64);
+        }
+        assert_eq!(values(&store, 0), [(3, 3), (4, 4)]);
+        assert_eq!(store.first_seq(), 3);
+        assert_eq!(store.next_seq(), 5);
+        assert_eq!(store.evicted(), 3);
+        assert_eq!(store.dropped(), 0);
+    }
+
+    #[test]
+    fn evicts_oldest_beyond_max_bytes() {
+        let retention = Retention {
+            max_len: None,
+            max_bytes: Some(10),
+        };
+        let mut store = Store::default();
+        store.push(Item(4), &retention);
+        store.push(Item(4), &retention);
+        assert_eq!(store.evicted(), 0);
+        store.push(Item(3), &retention);
+        assert_eq!(values(&store, 0), [(1, 4), (2, 3)]);
+
+        // An item too big on its own evicts everything, itself included.
+        store.push(Item(11), &retention);
+        assert_eq!(store.len(), 0);
+        assert_eq!(store.evicted(), 4);
+        assert_eq!(store.next_seq(), 4);
+
+        // Updates are accounted for, but only evict on the next push.
+        store.push(Item(2), &retention);
+        store.update(4, |item| item.0 = 9);
+        store.push(Item(1), &retention);
+        assert_eq!(values(&store, 0), [(4, 9), (5, 1)]);
+        store.push(Item(1), &retention);
+        assert_eq!(values(&store, 0), [(5, 1), (6, 1)]);
+    }
+
+    #[test]
+    fn counts_drops_apart_from_evictions() {
+        let retention = Retention {
+            max_len: Some(1),
+            max_bytes: None,
+        };
+        let mut store = Store::default();
+        store.push(Item(0), &retention);
+        store.push(Item(1), &retention);
+        store.add_dropped(5);
+        assert_eq!(store.evicted(), 1);
+        assert_eq!(store.dropped(), 5);
+        // Drops don't take up sequence numbers.
+        assert_eq!(store.next_seq(), 2);
+    }
+
+    #[test]
+    fn sequence_numbers_continue_across_clear() {
+        let retention = Retention {
+            max_len: Some(2),
+            max_bytes: None,
+        };
+        let mut store = Store::default();
+        for value in 0..3 {
+            store.push(Item(value), &retention);
+        }
+        store.add_dropped(1);
+        store.clear();
+        assert_eq!(store.len(), 0);
+        assert_eq!(store.first_seq(), 3);
+        assert_eq!(store.next_seq(), 3);
+        assert_eq!(store.evicted(), 0);
+        assert_eq!(store.dropped(), 0);
+        assert!(store.get(2).is_none());
+
+        // A reader's cursor from before the clear only sees the new items.
+        assert_eq!(store.push(Item(7), &retention), 3);
+        assert_eq!(values(&store, 1), [(3, 7)]);
+        assert_eq!(values(&store, 4), []);
+        assert_eq!(store.get(3), Some(&Arc::new(Item(7))));
+    }
+}
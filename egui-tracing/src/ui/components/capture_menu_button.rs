@@ -0,0 +1,127 @@
+use egui::{Align, Layout, Ui};
+use tracing::level_filters::LevelFilter;
+use tracing::Level;
+
+use crate::string::Ellipse;
+use crate::tracing::collector::EventCollector;
+
+const LEVELS: [Level; 5] = [
+    Level::TRACE,
+    Level::DEBUG,
+    Level::INFO,
+    Level::WARN,
+    Level::ERROR,
+];
+
+const LEVEL_FILTERS: [LevelFilter; 6] = [
+    LevelFilter::TRACE,
+    LevelFilter::DEBUG,
+    LevelFilter::INFO,
+    LevelFilter::WARN,
+    LevelFilter::ERROR,
+    LevelFilter::OFF,
+];
+
+/// Changes what the collector captures while the application is running.
+#[derive(Default)]
+pub struct CaptureMenuButton<'a> {
+    collector: Option<&'a EventCollector>,
+}
+
+impl<'a> CaptureMenuButton<'a> {
+    pub fn collector(mut self, v: &'a EventCollector) -> Self {
+        self.collector = Some(v);
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui) {
+        let collector = self.collector.unwrap();
+        ui.menu_button("Capture", |ui| {
+            ui.label("Capture Settings").on_hover_text(
+                "Levels captured by the collector. Events that weren't captured can't be shown.",
+            );
+
+            let mut config = collector.capture_config();
+            let mut changed = false;
+
+            ui.horizontal(|ui| {
+                ui.label("Default");
+                let default = config
+                    .directives
+                    .default_level()
+                    .unwrap_or_else(|| config.level.into());
+                ui.menu_button(default.to_string(), |ui| {
+                    for level in LEVELS {
+                        if ui
+                            .selectable_label(default == level, level.as_str())
+                            .clicked()
+                        {
+                            // A default from the directives would take precedence.
+                            config.directives.set_default(None);
+                            config.level = level;
+                            changed = true;
+                            ui.close_menu();
+                        }
+                    }
+                });
+            });
+
+            let input_id = ui.id().with("capture_input");
+            let mut input =
+                ui.data_mut(|data| data.get_temp::<String>(input_id).unwrap_or_default());
+            ui.horizontal(|ui| {
+                let response = ui
+                    .text_edit_singleline(&mut input)
+                    .on_hover_text("example: my_app::net");
+                let add = ui.add_enabled(!input.is_empty(), egui::Button::new("Add"));
+                if !input.is_empty()
+                    && (add.clicked()
+                        || (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter))))
+                {
+                    config.directives.set(&input, config.level.into());
+                    input.clear();
+                    changed = true;
+                }
+            });
+            ui.data_mut(|data| data.insert_temp(input_id, input));
+
+            let mut updated = None;
+            let mut removed = None;
+            for (target, level) in config.directives.targets() {
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(target.truncate_graphemes(18))
+                        .on_hover_text(target);
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.button("Delete").clicked() {
+                            removed = Some(target.to_owned());
+                        }
+                        ui.menu_button(level.to_string(), |ui| {
+                            for filter in LEVEL_FILTERS {
+                                if ui
+                                    .selectable_label(level == filter, filter.to_string())
+                                    .clicked()
+                                {
+                                    updated = Some((target.to_owned(), filter));
+                                    ui.close_menu();
+                                }
+                            }
+                        });
+                    });
+                });
+            }
+            if let Some((target, level)) = updated {
+                config.directives.set(&target, level);
+                changed = true;
+            }
+            if let Some(target) = removed {
+                config.directives.remove(&target);
+                changed = true;
+            }
+
+            if changed {
+                collector.set_capture_config(config);
+            }
+        });
+    }
+}
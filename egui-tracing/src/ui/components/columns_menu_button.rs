@@ -0,0 +1,25 @@
+use egui::Ui;
+
+use crate::ui::state::Columns;
+
+#[derive(Default)]
+pub struct ColumnsMenuButton<'a> {
+    state: Option<&'a mut Columns>,
+}
+
+impl<'a> ColumnsMenuButton<'a> {
+    pub fn state(mut self, v: &'a mut Columns) -> Self {
+        self.state = Some(v);
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui) {
+        let state = self.state.unwrap();
+        ui.menu_button("Columns", |ui| {
+            ui.label("Optional Columns");
+            ui.checkbox(&mut state.spans, "Spans");
+            ui.checkbox(&mut state.location, "Location");
+            ui.checkbox(&mut state.thread, "Thread");
+        });
+    }
+}
@@ -0,0 +1,125 @@
+use egui::{Color32, Grid, RichText, Ui};
+
+use crate::time::DateTimeFormatExt;
+use crate::tracing::CollectedEvent;
+use crate::ui::color::ToColor32;
+use crate::ui::state::DetailsPanel;
+
+#[derive(Default)]
+pub struct EventDetails<'a> {
+    event: Option<&'a CollectedEvent>,
+    position: Option<&'a mut DetailsPanel>,
+    on_close: Option<Box<dyn FnMut() + 'a>>,
+}
+
+impl<'a> EventDetails<'a> {
+    pub fn event(mut self, v: &'a CollectedEvent) -> Self {
+        self.event = Some(v);
+        self
+    }
+
+    pub fn position(mut self, v: &'a mut DetailsPanel) -> Self {
+        self.position = Some(v);
+        self
+    }
+
+    pub fn on_close(mut self, v: impl FnMut() + 'a) -> Self {
+        self.on_close = Some(Box::new(v));
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui) {
+        let event = self.event.unwrap();
+        let position = self.position.unwrap();
+
+        ui.horizontal(|ui| {
+            ui.style_mut().visuals.override_text_color = Some(Color32::WHITE);
+            ui.label("Event Details");
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button("Close").clicked() {
+                    (self.on_close.unwrap())();
+                }
+                let (label, other) = match position {
+                    DetailsPanel::Right => ("Dock Bottom", DetailsPanel::Bottom),
+                    DetailsPanel::Bottom => ("Dock Right", DetailsPanel::Right),
+                };
+                if ui.button(label).clicked() {
+                    *position = other;
+                }
+            });
+        });
+
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                Grid::new("event_details")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        value_row(ui, "time", event.time.format_detailed());
+                        ui.label("level");
+                        ui.colored_label(event.level.to_color32(), event.level.as_str());
+                        ui.end_row();
+                        value_row(ui, "target", event.target.clone());
+                        if let Some(location) = event.location() {
+                            value_row(ui, "location", location);
+                        }
+                        if let Some(module_path) = &event.module_path {
+                            value_row(ui, "module path", module_path.clone());
+                        }
+                        value_row(
+                            ui,
+                            "thread",
+                            format!(
+                                "{} ({})",
+                                event.thread.name.as_deref().unwrap_or("unnamed"),
+                                event.thread.id
+                            ),
+                        );
+                        if !event.spans.is_empty() {
+                            value_row(ui, "spans", event.span_breadcrumb());
+                        }
+                    });
+
+                ui.add_space(8.0);
+                ui.label(RichText::new("Fields").color(Color32::WHITE));
+                Grid::new("event_details_fields")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (key, value) in &event.fields {
+                            value_row(ui, key, value.to_string());
+                        }
+                    });
+
+                for span in event.spans.iter().rev() {
+                    ui.add_space(8.0);
+                    ui.label(RichText::new(format!("Span {}", span.name)).color(Color32::WHITE))
+                        .on_hover_text(&span.target);
+                    Grid::new(("event_details_span", span.id))
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (key, value) in &span.fields {
+                                value_row(ui, key, value.to_string());
+                            }
+                        });
+                }
+            });
+    }
+}
+
+fn value_row(ui: &mut Ui, key: &str, value: String) {
+    ui.label(key);
+    ui.add(egui::Label::new(&value).wrap(true));
+    if ui
+        .small_button("Copy")
+        .on_hover_text("Copy Value")
+        .clicked()
+    {
+        ui.output_mut(|o| o.copied_text = value);
+    }
+    ui.end_row();
+}
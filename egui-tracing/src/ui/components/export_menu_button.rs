@@ -0,0 +1,66 @@
+use egui::{Color32, Ui};
+
+use crate::ui::state::ExportState;
+
+type OnExport<'a> = Box<dyn FnMut(&str, bool) + 'a>;
+
+#[derive(Default)]
+pub struct ExportMenuButton<'a> {
+    state: Option<&'a mut ExportState>,
+    running: bool,
+    on_export: Option<OnExport<'a>>,
+}
+
+impl<'a> ExportMenuButton<'a> {
+    pub fn state(mut self, v: &'a mut ExportState) -> Self {
+        self.state = Some(v);
+        self
+    }
+
+    /// Whether an export is still being written.
+    pub fn running(mut self, v: bool) -> Self {
+        self.running = v;
+        self
+    }
+
+    /// Called with the path and whether to export only the filtered events,
+    /// to start an export. Its outcome is reported in [`ExportState::result`].
+    pub fn on_export(mut self, v: impl FnMut(&str, bool) + 'a) -> Self {
+        self.on_export = Some(Box::new(v));
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui) {
+        let state = self.state.unwrap();
+        let mut on_export = self.on_export.unwrap();
+        ui.menu_button("Export", |ui| {
+            ui.label("Export as JSON Lines");
+            ui.text_edit_singleline(&mut state.path)
+                .on_hover_text("Path of the file to write");
+            ui.checkbox(&mut state.filtered, "Only filtered events");
+            let enabled = !state.path.is_empty() && !self.running;
+            if ui
+                .add_enabled(enabled, egui::Button::new("Export"))
+                .clicked()
+            {
+                state.result = None;
+                on_export(&state.path, state.filtered);
+            }
+            if self.running {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Exporting…");
+                });
+            }
+            match &state.result {
+                Some(Ok(message)) => {
+                    ui.label(message);
+                }
+                Some(Err(error)) => {
+                    ui.colored_label(Color32::RED, error);
+                }
+                None => {}
+            }
+        });
+    }
+}
@@ -1,6 +1,13 @@
+pub mod capture_menu_button;
+pub mod columns_menu_button;
 pub mod common;
 pub mod constants;
+pub mod event_details;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod export_menu_button;
 pub mod level_menu_button;
+pub mod query_input;
+pub mod search_bar;
 pub mod table;
 pub mod table_cell;
 pub mod table_header;
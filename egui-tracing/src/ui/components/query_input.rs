@@ -0,0 +1,38 @@
+use egui::{Color32, Ui};
+
+#[derive(Default)]
+pub struct QueryInput<'a> {
+    state: Option<&'a mut String>,
+    error: Option<String>,
+}
+
+impl<'a> QueryInput<'a> {
+    pub fn state(mut self, v: &'a mut String) -> Self {
+        self.state = Some(v);
+        self
+    }
+
+    pub fn error(mut self, v: Option<String>) -> Self {
+        self.error = v;
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui) {
+        let state = self.state.unwrap();
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.add(
+                egui::TextEdit::singleline(state)
+                    .hint_text("level>=warn AND target:my_app::*")
+                    .desired_width(ui.available_width().min(320.0)),
+            )
+            .on_hover_text(
+                "Compare `level`, `target`, `module`, `file`, `thread`, `span` or any field \
+                 with =, !=, >, >=, <, <=, : (glob) or ~ (regex), combined with AND, OR, NOT.",
+            );
+            if let Some(error) = self.error {
+                ui.colored_label(Color32::RED, error);
+            }
+        });
+    }
+}
@@ -0,0 +1,85 @@
+use egui::{Color32, Ui};
+
+use crate::ui::state::Search;
+
+#[derive(Default)]
+pub struct SearchBar<'a> {
+    state: Option<&'a mut Search>,
+    error: Option<String>,
+    current: Option<usize>,
+    rows: usize,
+    on_previous: Option<Box<dyn FnMut() + 'a>>,
+    on_next: Option<Box<dyn FnMut() + 'a>>,
+}
+
+impl<'a> SearchBar<'a> {
+    pub fn state(mut self, v: &'a mut Search) -> Self {
+        self.state = Some(v);
+        self
+    }
+
+    pub fn error(mut self, v: Option<String>) -> Self {
+        self.error = v;
+        self
+    }
+
+    /// Index of the selected row and the number of rows passing all filters,
+    /// the search being one of them.
+    pub fn rows(mut self, current: Option<usize>, total: usize) -> Self {
+        self.current = current;
+        self.rows = total;
+        self
+    }
+
+    pub fn on_previous(mut self, v: impl FnMut() + 'a) -> Self {
+        self.on_previous = Some(Box::new(v));
+        self
+    }
+
+    pub fn on_next(mut self, v: impl FnMut() + 'a) -> Self {
+        self.on_next = Some(Box::new(v));
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui) {
+        let state = self.state.unwrap();
+        ui.horizontal(|ui| {
+            ui.label("Search");
+            ui.add(
+                egui::TextEdit::singleline(&mut state.text)
+                    .hint_text(if state.regex { "regex" } else { "text" })
+                    .desired_width(200.0),
+            );
+            ui.toggle_value(&mut state.regex, ".*")
+                .on_hover_text("Regular Expression");
+            ui.toggle_value(&mut state.case_sensitive, "Aa")
+                .on_hover_text("Case Sensitive");
+            ui.toggle_value(&mut state.all_fields, "Fields")
+                .on_hover_text("Search All Fields");
+
+            if let Some(error) = self.error {
+                ui.colored_label(Color32::RED, "invalid regex")
+                    .on_hover_text(error);
+                return;
+            }
+            if state.text.is_empty() {
+                return;
+            }
+
+            ui.separator();
+            let current = self
+                .current
+                .map_or_else(|| "-".to_owned(), |current| (current + 1).to_string());
+            ui.label(format!("{}/{} rows", current, self.rows))
+                .on_hover_text("Selected row of the rows passing all filters");
+            ui.add_enabled_ui(self.rows > 0, |ui| {
+                if ui.button("<").on_hover_text("Previous Row").clicked() {
+                    (self.on_previous.unwrap())();
+                }
+                if ui.button(">").on_hover_text("Next Row").clicked() {
+                    (self.on_next.unwrap())();
+                }
+            });
+        });
+    }
+}
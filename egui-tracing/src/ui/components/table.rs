@@ -1,33 +1,63 @@
-use std::slice::Iter;
-
-use egui::{Color32, Response, Ui};
+use egui::{Align, Color32, Layout, Response, Sense, Shape, Ui};
 
+use super::common::Children;
 use super::constants::SEPARATOR_SPACING;
 
+type Row<'a, T> = Box<dyn FnMut(&mut Ui, &T) + 'a>;
+type OnRowClicked<'a, T> = Box<dyn FnMut(&T) + 'a>;
+
 pub struct Table<'a, T> {
     row_height: Option<f32>,
+    status: Option<String>,
+    toolbar: Option<Children<'a>>,
+    actions: Option<Children<'a>>,
     on_clear: Option<Box<dyn FnMut() + 'a>>,
-    header: Option<Box<dyn FnMut(&mut Ui) + 'a>>,
-    row: Option<Box<dyn FnMut(&mut Ui, &T) + 'a>>,
+    header: Option<Children<'a>>,
+    row: Option<Row<'a, T>>,
+    selected: Option<T>,
+    scroll_to_row: Option<usize>,
+    on_row_clicked: Option<OnRowClicked<'a, T>>,
 }
 
 impl<'a, T> Default for Table<'a, T> {
     fn default() -> Self {
         Self {
             row_height: None,
+            status: None,
+            toolbar: None,
+            actions: None,
             on_clear: None,
             header: None,
             row: None,
+            selected: None,
+            scroll_to_row: None,
+            on_row_clicked: None,
         }
     }
 }
 
-impl<'a, T> Table<'a, T> {
+impl<'a, T: PartialEq> Table<'a, T> {
     pub fn row_height(mut self, v: f32) -> Self {
         self.row_height = Some(v);
         self
     }
 
+    pub fn status(mut self, v: impl Into<String>) -> Self {
+        self.status = Some(v.into());
+        self
+    }
+
+    pub fn toolbar(mut self, v: impl FnMut(&mut Ui) + 'a) -> Self {
+        self.toolbar = Some(Box::new(v));
+        self
+    }
+
+    /// Buttons shown next to "Clear".
+    pub fn actions(mut self, v: impl FnMut(&mut Ui) + 'a) -> Self {
+        self.actions = Some(Box::new(v));
+        self
+    }
+
     pub fn on_clear(mut self, v: impl FnMut() + 'a) -> Self {
         self.on_clear = Some(Box::new(v));
         self
@@ -43,7 +73,22 @@ impl<'a, T> Table<'a, T> {
         self
     }
 
-    pub fn show(self, ui: &mut Ui, values: Iter<&T>) -> Response {
+    pub fn selected(mut self, v: Option<T>) -> Self {
+        self.selected = v;
+        self
+    }
+
+    pub fn scroll_to_row(mut self, v: Option<usize>) -> Self {
+        self.scroll_to_row = v;
+        self
+    }
+
+    pub fn on_row_clicked(mut self, v: impl FnMut(&T) + 'a) -> Self {
+        self.on_row_clicked = Some(Box::new(v));
+        self
+    }
+
+    pub fn show(self, ui: &mut Ui, values: &[T]) -> Response {
         ui.vertical(|ui| {
             ui.horizontal(|ui| {
                 ui.style_mut().visuals.override_text_color = Some(Color32::WHITE);
@@ -52,51 +97,93 @@ impl<'a, T> Table<'a, T> {
                     (self.header.unwrap())(ui);
                 });
 
-                ui.add_space(ui.available_width() - 130.0);
-
-                if ui
-                    .button("To Bottom")
-                    .on_hover_text("Scroll to Bottom")
-                    .clicked()
-                {
-                    ui.scroll_to_rect(
-                        egui::Rect {
-                            min: egui::Pos2 { x: 0.0, y: 0.0 },
-                            max: egui::Pos2 {
-                                x: f32::MAX,
-                                y: f32::MAX,
-                            },
-                        },
-                        Some(egui::Align::Max),
-                    );
+                if let Some(status) = self.status {
+                    ui.colored_label(Color32::GRAY, status);
                 }
 
-                ui.separator();
-
-                if ui.button("Clear").on_hover_text("Clear Events").clicked() {
-                    (self.on_clear.unwrap())();
-                }
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    if ui.button("Clear").on_hover_text("Clear Events").clicked() {
+                        (self.on_clear.unwrap())();
+                    }
+
+                    if let Some(mut actions) = self.actions {
+                        actions(ui);
+                    }
+
+                    ui.separator();
+
+                    if ui
+                        .button("To Bottom")
+                        .on_hover_text("Scroll to Bottom")
+                        .clicked()
+                    {
+                        ui.scroll_to_rect(
+                            egui::Rect {
+                                min: egui::Pos2 { x: 0.0, y: 0.0 },
+                                max: egui::Pos2 {
+                                    x: f32::MAX,
+                                    y: f32::MAX,
+                                },
+                            },
+                            Some(egui::Align::Max),
+                        );
+                    }
+
+                    if let Some(mut toolbar) = self.toolbar {
+                        ui.separator();
+                        toolbar(ui);
+                    }
+                });
             });
 
             ui.separator();
 
             let mut row = self.row.unwrap();
-            egui::ScrollArea::vertical()
+            let mut on_row_clicked = self.on_row_clicked;
+            let selected = self.selected;
+            let row_height = self.row_height.unwrap() + SEPARATOR_SPACING;
+            let mut scroll_area = egui::ScrollArea::vertical()
                 .auto_shrink([false, false])
-                .stick_to_bottom(true)
-                .show_rows(
-                    ui,
-                    self.row_height.unwrap() + SEPARATOR_SPACING,
-                    values.len(),
-                    |ui, range| {
-                        for value in values.skip(range.start).take(range.len()) {
-                            ui.horizontal(|ui| {
-                                row(ui, value);
-                            });
-                            ui.separator();
+                .stick_to_bottom(true);
+            if let Some(index) = self.scroll_to_row {
+                let row_top = index as f32 * (row_height + ui.spacing().item_spacing.y);
+                let offset = row_top - ui.available_height() / 2.0;
+                scroll_area = scroll_area.vertical_scroll_offset(offset.max(0.0));
+            }
+            scroll_area.show_rows(ui, row_height, values.len(), |ui, range| {
+                ui.style_mut().interaction.selectable_labels = false;
+                for value in &values[range] {
+                    let background = ui.painter().add(Shape::Noop);
+                    let response = ui
+                        .horizontal(|ui| {
+                            ui.set_min_width(ui.available_width());
+                            row(ui, value);
+                        })
+                        .response
+                        .interact(Sense::click());
+
+                    let visuals = ui.visuals();
+                    let fill = if selected.as_ref() == Some(value) {
+                        Some(visuals.selection.bg_fill)
+                    } else if response.hovered() {
+                        Some(visuals.widgets.hovered.weak_bg_fill)
+                    } else {
+                        None
+                    };
+                    if let Some(fill) = fill {
+                        let rect = response.rect.expand2(egui::vec2(0.0, 2.0));
+                        ui.painter()
+                            .set(background, Shape::rect_filled(rect, 0.0, fill));
+                    }
+
+                    if response.clicked() {
+                        if let Some(on_row_clicked) = on_row_clicked.as_mut() {
+                            on_row_clicked(value);
                         }
-                    },
-                )
+                    }
+                    ui.separator();
+                }
+            })
         })
         .response
     }
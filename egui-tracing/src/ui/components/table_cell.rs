@@ -1,12 +1,12 @@
 use egui::{Response, Ui};
 
-use super::common::{set_common_props, CommonProps};
+use super::common::{set_common_props, Children, CommonProps};
 use super::table_header;
 
 #[derive(Default)]
 pub struct TableCell<'a> {
     common_props: Option<CommonProps>,
-    children: Option<Box<dyn FnMut(&mut Ui) + 'a>>,
+    children: Option<Children<'a>>,
 }
 
 impl<'a> TableCell<'a> {
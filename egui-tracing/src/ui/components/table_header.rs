@@ -1,13 +1,13 @@
 use egui::{vec2, Response, Sense, Ui};
 
-use super::common::{set_common_props, CommonProps};
+use super::common::{set_common_props, Children, CommonProps};
 
 pub static PADDING_LEFT: f32 = 4.0;
 
 #[derive(Default)]
 pub struct TableHeader<'a> {
     common_props: Option<CommonProps>,
-    children: Option<Box<dyn FnMut(&mut Ui) + 'a>>,
+    children: Option<Children<'a>>,
 }
 
 impl<'a> TableHeader<'a> {
@@ -1,12 +1,13 @@
-use egui::Ui;
+use egui::{Color32, Ui};
 use globset::Glob;
 
 use super::target_menu_item::TargetMenuItem;
-use crate::ui::state::TargetFilter;
+use crate::ui::state::{TargetFilter, TargetRule};
 
 #[derive(Default)]
 pub struct TargetMenuButton<'a> {
     state: Option<&'a mut TargetFilter>,
+    error: Option<String>,
 }
 
 impl<'a> TargetMenuButton<'a> {
@@ -15,35 +16,75 @@ impl<'a> TargetMenuButton<'a> {
         self
     }
 
+    /// Error of compiling the current rules, shown inside the menu.
+    pub fn error(mut self, v: Option<String>) -> Self {
+        self.error = v;
+        self
+    }
+
     pub fn show(self, ui: &mut Ui) {
         let state = self.state.unwrap();
         ui.menu_button("Target", |ui| {
-            ui.label("Target Filter");
+            ui.label("Target Filter")
+                .on_hover_text("Only included targets are shown if there are any include rules. Exclude rules always win.");
 
+            let glob = Glob::new(&state.input);
             let (input, add_button) = ui
                 .horizontal(|ui| {
                     let input = ui
                         .text_edit_singleline(&mut state.input)
                         .on_hover_text("example: eframe::*");
-                    let button = ui.button("Add");
+                    if ui.button(state.input_mode.as_str()).clicked() {
+                        state.input_mode = state.input_mode.toggled();
+                    }
+                    let button =
+                        ui.add_enabled(!state.input.is_empty() && glob.is_ok(), egui::Button::new("Add"));
                     (input, button)
                 })
                 .inner;
 
-            if add_button.clicked()
-                || (input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
-            {
-                state.targets.push(Glob::new(&state.input).unwrap());
-                state.input = "".to_owned();
+            match glob {
+                Ok(glob) => {
+                    if add_button.clicked()
+                        || (!state.input.is_empty()
+                            && input.lost_focus()
+                            && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+                    {
+                        state.targets.push(TargetRule {
+                            glob,
+                            mode: state.input_mode,
+                        });
+                        state.input = "".to_owned();
+                    }
+                }
+                Err(err) if !state.input.is_empty() => {
+                    ui.colored_label(Color32::RED, err.kind().to_string());
+                }
+                Err(_) => {}
             }
 
-            for (i, target) in state.targets.clone().iter().enumerate() {
+            if let Some(error) = self.error {
+                ui.colored_label(Color32::RED, error);
+            }
+
+            let mut removed = None;
+            for (i, target) in state.targets.iter_mut().enumerate() {
+                let mut remove = false;
+                let mut toggle = false;
                 TargetMenuItem::default()
-                    .on_clicked(|| {
-                        state.targets.remove(i);
-                    })
-                    .target(&target)
+                    .on_clicked(|| remove = true)
+                    .on_toggled(|| toggle = true)
+                    .target(target)
                     .show(ui);
+                if toggle {
+                    target.mode = target.mode.toggled();
+                }
+                if remove {
+                    removed = Some(i);
+                }
+            }
+            if let Some(i) = removed {
+                state.targets.remove(i);
             }
         });
     }
@@ -1,16 +1,17 @@
-use egui::Ui;
-use globset::Glob;
+use egui::{Align, Layout, Ui};
 
 use crate::string::Ellipse;
+use crate::ui::state::TargetRule;
 
 #[derive(Default)]
 pub struct TargetMenuItem<'a> {
-    target: Option<&'a Glob>,
+    target: Option<&'a TargetRule>,
     on_clicked: Option<Box<dyn FnMut() + 'a>>,
+    on_toggled: Option<Box<dyn FnMut() + 'a>>,
 }
 
 impl<'a> TargetMenuItem<'a> {
-    pub fn target(mut self, v: &'a Glob) -> Self {
+    pub fn target(mut self, v: &'a TargetRule) -> Self {
         self.target = Some(v);
         self
     }
@@ -20,16 +21,30 @@ impl<'a> TargetMenuItem<'a> {
         self
     }
 
+    pub fn on_toggled(mut self, v: impl FnMut() + 'a) -> Self {
+        self.on_toggled = Some(Box::new(v));
+        self
+    }
+
     pub fn show(self, ui: &mut Ui) {
         ui.separator();
-        let pattern = self.target.unwrap().glob().to_owned();
+        let target = self.target.unwrap();
+        let pattern = target.glob.glob().to_owned();
         ui.horizontal(|ui| {
+            if ui
+                .button(target.mode.as_str())
+                .on_hover_text("Toggle between including and excluding")
+                .clicked()
+            {
+                self.on_toggled.unwrap()();
+            }
             ui.label(pattern.truncate_graphemes(18))
                 .on_hover_text(pattern);
-            ui.add_space(ui.available_width() - 43.0);
-            if ui.button("Delete").clicked() {
-                self.on_clicked.unwrap()();
-            }
+            ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                if ui.button("Delete").clicked() {
+                    self.on_clicked.unwrap()();
+                }
+            });
         });
     }
 }
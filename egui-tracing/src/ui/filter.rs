@@ -0,0 +1,351 @@
+use std::sync::Arc;
+
+use globset::{GlobSet, GlobSetBuilder};
+use regex::Regex;
+
+use super::query::Query;
+use super::search;
+use super::state::{LevelFilter, LogsState, Search, TargetFilter, TargetMode, TargetRule};
+use crate::tracing::{CollectedEvent, EventCollector};
+
+/// The parts of [`LogsState`] an event is filtered on.
+#[derive(Debug, Clone, PartialEq)]
+struct FilterKey {
+    level_filter: LevelFilter,
+    targets: Vec<TargetRule>,
+    search: Search,
+    query: String,
+}
+
+impl FilterKey {
+    fn new(state: &LogsState) -> Self {
+        Self {
+            level_filter: state.level_filter.clone(),
+            targets: state.target_filter.targets.clone(),
+            search: state.search.clone(),
+            query: state.query.clone(),
+        }
+    }
+
+    fn is_for(&self, state: &LogsState) -> bool {
+        self.level_filter == state.level_filter
+            && self.targets == state.target_filter.targets
+            && self.search == state.search
+            && self.query == state.query
+    }
+}
+
+/// Compiled include and exclude rules of a [`TargetFilter`].
+#[derive(Debug)]
+pub struct TargetMatcher {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+}
+
+impl TargetMatcher {
+    pub fn new(filter: &TargetFilter) -> Result<Self, globset::Error> {
+        let mut include = GlobSetBuilder::new();
+        let mut exclude = GlobSetBuilder::new();
+        let mut has_include = false;
+        for rule in &filter.targets {
+            match rule.mode {
+                TargetMode::Include => {
+                    has_include = true;
+                    include.add(rule.glob.clone());
+                }
+                TargetMode::Exclude => {
+                    exclude.add(rule.glob.clone());
+                }
+            }
+        }
+        Ok(Self {
+            include: if has_include {
+                Some(include.build()?)
+            } else {
+                None
+            },
+            exclude: exclude.build()?,
+        })
+    }
+
+    /// Excluding rules take precedence over including ones.
+    pub fn is_match(&self, target: &str) -> bool {
+        self.include
+            .as_ref()
+            .map_or(true, |include| include.is_match(target))
+            && !self.exclude.is_match(target)
+    }
+}
+
+/// The filters of a [`LogsState`], compiled once per change of the filters.
+/// Invalid filters are ignored and their error kept for display.
+#[derive(Debug, Default)]
+pub struct CompiledFilter {
+    level_filter: LevelFilter,
+    targets: Option<TargetMatcher>,
+    search: Option<Regex>,
+    all_fields: bool,
+    query: Option<Query>,
+    pub targets_error: Option<String>,
+    pub search_error: Option<String>,
+    pub query_error: Option<String>,
+}
+
+impl CompiledFilter {
+    fn new(state: &LogsState) -> Self {
+        let (targets, targets_error) = match TargetMatcher::new(&state.target_filter) {
+            Ok(targets) => (Some(targets), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        let (search, search_error) = match search::compile(&state.search) {
+            Ok(regex) => (regex, None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        let (query, query_error) = match Query::parse(&state.query) {
+            Ok(query) => (query, None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        Self {
+            level_filter: state.level_filter.clone(),
+            targets,
+            search,
+            all_fields: state.search.all_fields,
+            query,
+            targets_error,
+            search_error,
+            query_error,
+        }
+    }
+
+    pub fn matches(&self, event: &CollectedEvent) -> bool {
+        self.level_filter.get(event.level)
+            && self
+                .targets
+                .as_ref()
+                .map_or(true, |targets| targets.is_match(&event.target))
+            && self
+                .search
+                .as_ref()
+                .map_or(true, |regex| search::matches(regex, self.all_fields, event))
+            && self
+                .query
+                .as_ref()
+                .map_or(true, |query| query.matches(event))
+    }
+
+    /// The compiled search, used to highlight matches.
+    pub fn search(&self) -> Option<&Regex> {
+        self.search.as_ref()
+    }
+}
+
+/// The events passing the filters and their sequence numbers, extended with
+/// new events every frame and only recomputed when the filters or the
+/// collector change. Keeps the events, so they can be shown without locking
+/// the store.
+#[derive(Debug, Default)]
+pub struct FilteredIndex {
+    key: Option<FilterKey>,
+    collector: usize,
+    filter: CompiledFilter,
+    cursor: u64,
+    seqs: Vec<u64>,
+    events: Vec<Arc<CollectedEvent>>,
+}
+
+impl FilteredIndex {
+    /// Only takes the new events from the store while it's locked, and
+    /// filters them after releasing it, so that refiltering all events
+    /// doesn't block the logging threads.
+    pub fn update(&mut self, collector: &EventCollector, state: &LogsState) {
+        if !self.key.as_ref().is_some_and(|key| key.is_for(state))
+            || self.collector != collector.id()
+        {
+            self.key = Some(FilterKey::new(state));
+            self.collector = collector.id();
+            self.filter = CompiledFilter::new(state);
+            self.reset();
+        }
+
+        let (first_seq, new) = {
+            let events = collector.read();
+            if self.cursor > events.next_seq() {
+                self.reset();
+            }
+            let new: Vec<_> = events.iter_shared_since(self.cursor).collect();
+            self.cursor = events.next_seq();
+            (events.first_seq(), new)
+        };
+
+        let evicted = self.seqs.partition_point(|seq| *seq < first_seq);
+        self.seqs.drain(..evicted);
+        self.events.drain(..evicted);
+
+        for (seq, event) in new {
+            if self.filter.matches(&event) {
+                self.seqs.push(seq);
+                self.events.push(event);
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.cursor = 0;
+        self.seqs.clear();
+        self.events.clear();
+    }
+
+    pub fn filter(&self) -> &CompiledFilter {
+        &self.filter
+    }
+
+    pub fn seqs(&self) -> &[u64] {
+        &self.seqs
+    }
+
+    pub fn get(&self, seq: u64) -> Option<&Arc<CollectedEvent>> {
+        let index = self.seqs.binary_search(&seq).ok()?;
+        self.events.get(index)
+    }
+
+    pub fn events(&self) -> &[Arc<CollectedEvent>] {
+        &self.events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use chrono::Local;
+    use tracing::Level;
+
+    use super::*;
+    use crate::tracing::ThreadInfo;
+    use crate::ui::state::TargetRule;
+
+    fn matcher(rules: &[&str]) -> TargetMatcher {
+        let targets = rules
+            .iter()
+            .map(|rule| match rule.strip_prefix('!') {
+                Some(glob) => TargetRule::exclude(glob),
+                None => TargetRule::include(rule),
+            })
+            .collect::<Result<_, _>>()
+            .unwrap();
+        TargetMatcher::new(&TargetFilter::new(targets)).unwrap()
+    }
+
+    #[test]
+    fn no_rules_match_everything() {
+        let matcher = matcher(&[]);
+        assert!(matcher.is_match("my_app"));
+        assert!(matcher.is_match("hyper::proto"));
+        assert!(matcher.is_match(""));
+    }
+
+    #[test]
+    fn includes_match_only_their_targets() {
+        let matcher = matcher(&["my_app::*", "tower"]);
+        assert!(matcher.is_match("my_app::db"));
+        assert!(matcher.is_match("my_app::net::tcp"));
+        assert!(matcher.is_match("tower"));
+        assert!(!matcher.is_match("my_app"));
+        assert!(!matcher.is_match("hyper::proto"));
+    }
+
+    #[test]
+    fn excludes_hide_only_their_targets() {
+        let matcher = matcher(&["!hyper::*", "!mio"]);
+        assert!(!matcher.is_match("hyper::proto"));
+        assert!(!matcher.is_match("mio"));
+        assert!(matcher.is_match("hyper"));
+        assert!(matcher.is_match("my_app::db"));
+    }
+
+    #[test]
+    fn excludes_take_precedence_over_includes() {
+        // Regardless of the order of the rules.
+        for rules in [
+            ["my_app::*", "!my_app::net::*"],
+            ["!my_app::net::*", "my_app::*"],
+        ] {
+            let matcher = matcher(&rules);
+            assert!(matcher.is_match("my_app::db"));
+            assert!(!matcher.is_match("my_app::net::tcp"));
+            assert!(!matcher.is_match("hyper::proto"));
+        }
+    }
+
+    fn event(target: &str) -> CollectedEvent {
+        CollectedEvent {
+            target: target.to_owned(),
+            level: Level::INFO,
+            fields: BTreeMap::new(),
+            time: Local::now(),
+            spans: Vec::new(),
+            file: None,
+            line: None,
+            module_path: None,
+            thread: ThreadInfo {
+                name: None,
+                id: "ThreadId(1)".to_owned(),
+            },
+        }
+    }
+
+    fn targets(index: &FilteredIndex) -> Vec<(u64, &str)> {
+        index
+            .seqs()
+            .iter()
+            .zip(index.events())
+            .map(|(seq, event)| (*seq, event.target.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn index_follows_the_store() {
+        let collector = EventCollector::new().with_max_events(2);
+        let mut state = LogsState {
+            target_filter: TargetFilter::new(vec![TargetRule::exclude("hyper").unwrap()]),
+            ..Default::default()
+        };
+        let mut index = FilteredIndex::default();
+
+        collector.import(event("my_app"));
+        collector.import(event("hyper"));
+        index.update(&collector, &state);
+        assert_eq!(targets(&index), [(0, "my_app")]);
+
+        // Evicted events are removed, new ones filtered.
+        collector.import(event("my_app::db"));
+        collector.import(event("hyper"));
+        index.update(&collector, &state);
+        assert_eq!(targets(&index), [(2, "my_app::db")]);
+
+        // Changed filters apply to all stored events.
+        state.target_filter.targets.clear();
+        index.update(&collector, &state);
+        assert_eq!(targets(&index), [(2, "my_app::db"), (3, "hyper")]);
+    }
+
+    #[test]
+    fn index_is_rebuilt_for_another_collector() {
+        let state = LogsState::default();
+        let mut index = FilteredIndex::default();
+        let first = EventCollector::new();
+        for target in ["a", "b", "c"] {
+            first.import(event(target));
+        }
+        index.update(&first, &state);
+
+        // The sequence numbers of the new store start over, so none of its
+        // events may be skipped for having been read from the old one.
+        let second = EventCollector::new();
+        for target in ["d", "e", "f", "g"] {
+            second.import(event(target));
+        }
+        index.update(&second, &state);
+        assert_eq!(targets(&index), [(0, "d"), (1, "e"), (2, "f"), (3, "g")]);
+    }
+}
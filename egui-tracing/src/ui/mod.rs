@@ -1,70 +1,360 @@
 mod color;
 mod components;
+mod filter;
+mod query;
+mod search;
+mod spans;
 mod state;
 
+use std::cell::Cell;
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+use std::hash::Hash;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::BufWriter;
 use std::sync::{Arc, Mutex};
 
-use egui::{Color32, Label, Response, TextStyle, Widget};
-use globset::GlobSetBuilder;
+use egui::{Color32, Id, Label, Response, TextStyle, Widget};
 
 use self::color::ToColor32;
+use self::components::capture_menu_button::CaptureMenuButton;
+use self::components::columns_menu_button::ColumnsMenuButton;
 use self::components::common::CommonProps;
 use self::components::constants;
+use self::components::event_details::EventDetails;
+#[cfg(not(target_arch = "wasm32"))]
+use self::components::export_menu_button::ExportMenuButton;
 use self::components::level_menu_button::LevelMenuButton;
+use self::components::query_input::QueryInput;
+use self::components::search_bar::SearchBar;
 use self::components::table::Table;
 use self::components::table_cell::TableCell;
 use self::components::table_header::TableHeader;
 use self::components::target_menu_button::TargetMenuButton;
-use self::state::LogsState;
+use self::filter::FilteredIndex;
+pub use self::spans::Spans;
+pub use self::state::{
+    Columns, DetailsPanel, ExportState, LevelFilter, LogsState, Search, TargetFilter, TargetMode,
+    TargetRule,
+};
 use crate::string::Ellipse;
 use crate::time::DateTimeFormatExt;
 use crate::tracing::collector::EventCollector;
-use crate::tracing::CollectedEvent;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tracing::json::write_jsonl;
 
+/// Table of the events collected by an [`EventCollector`].
+///
+/// Filters, columns and search settings are kept in egui's persisted memory,
+/// so they survive restarts of apps with persistence enabled (see this
+/// crate's `persistence` feature).
 pub struct Logs {
     collector: EventCollector,
+    id_source: Option<Id>,
+    default_state: Option<LogsState>,
+    level_filter: Option<LevelFilter>,
+    target_filter: Option<TargetFilter>,
+    search: Option<Search>,
+    query: Option<String>,
+    selected: Option<Option<u64>>,
+}
+
+/// What [`Logs::show`] returns.
+pub struct LogsOutput {
+    pub response: Response,
+    /// The widget's state at the end of the frame.
+    pub state: LogsState,
+}
+
+/// Export being written in the background, kept in egui's temp memory.
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct ExportTask {
+    running: bool,
+    /// Outcome of a finished export that wasn't shown yet.
+    finished: Option<Result<String, String>>,
 }
 
 impl Logs {
     #[must_use]
     pub const fn new(collector: EventCollector) -> Self {
-        Self { collector }
+        Self {
+            collector,
+            id_source: None,
+            default_state: None,
+            level_filter: None,
+            target_filter: None,
+            search: None,
+            query: None,
+            selected: None,
+        }
+    }
+
+    /// Keys the widget's state on `id_source` instead of the parent [`egui::Ui`],
+    /// needed to show multiple independent log views in the same parent.
+    #[must_use]
+    pub fn id_source(mut self, id_source: impl Hash) -> Self {
+        self.id_source = Some(Id::new(id_source));
+        self
+    }
+
+    /// State to start with when none has been stored for this widget yet.
+    #[must_use]
+    pub fn default_state(mut self, state: LogsState) -> Self {
+        self.default_state = Some(state);
+        self
+    }
+
+    /// Replaces the stored level filter. Like the other setters below, this
+    /// should only be called on the frame the filter is meant to change, as
+    /// it overrides the user's choice otherwise.
+    #[must_use]
+    pub fn level_filter(mut self, level_filter: LevelFilter) -> Self {
+        self.level_filter = Some(level_filter);
+        self
+    }
+
+    /// Replaces the stored target filter.
+    #[must_use]
+    pub fn target_filter(mut self, target_filter: TargetFilter) -> Self {
+        self.target_filter = Some(target_filter);
+        self
+    }
+
+    /// Replaces the stored search.
+    #[must_use]
+    pub fn search(mut self, search: Search) -> Self {
+        self.search = Some(search);
+        self
+    }
+
+    /// Replaces the stored filter expression.
+    #[must_use]
+    pub fn query(mut self, query: impl Into<String>) -> Self {
+        self.query = Some(query.into());
+        self
+    }
+
+    /// Selects the event with the given sequence number, see
+    /// [`EventCollector::read`], or clears the selection.
+    #[must_use]
+    pub fn selected(mut self, selected: Option<u64>) -> Self {
+        self.selected = Some(selected);
+        self
+    }
+
+    /// Shows the widget, returning its state along with the response.
+    pub fn show(mut self, ui: &mut egui::Ui) -> LogsOutput {
+        match self.id_source.take() {
+            Some(id_source) => ui.push_id(id_source, |ui| self.show_logs(ui)).inner,
+            None => self.show_logs(ui),
+        }
     }
 }
 
 impl Widget for Logs {
     fn ui(self, ui: &mut egui::Ui) -> Response {
-        let state = ui.memory_mut(|mem| {
-            let state_mem_id = ui.id();
-            mem.data
-                .get_temp_mut_or_insert_with(state_mem_id, || {
-                    Arc::new(Mutex::new(LogsState::default()))
-                })
-                .clone()
+        self.show(ui).response
+    }
+}
+
+impl Logs {
+    fn show_logs(mut self, ui: &mut egui::Ui) -> LogsOutput {
+        let state_mem_id = ui.id();
+        // Persisted, so the filters survive restarts when egui's `persistence`
+        // feature is enabled. The filtered index is only a cache.
+        let mut state = ui
+            .data_mut(|data| data.get_persisted::<LogsState>(state_mem_id))
+            .unwrap_or_else(|| self.default_state.take().unwrap_or_default());
+        if let Some(level_filter) = self.level_filter.take() {
+            state.level_filter = level_filter;
+        }
+        if let Some(target_filter) = self.target_filter.take() {
+            state.target_filter = target_filter;
+        }
+        if let Some(search) = self.search.take() {
+            state.search = search;
+        }
+        if let Some(query) = self.query.take() {
+            state.query = query;
+        }
+        if let Some(selected) = self.selected.take() {
+            state.selected = selected;
+        }
+        let index = ui.data_mut(|data| {
+            data.get_temp_mut_or_insert_with(state_mem_id.with("index"), || {
+                Arc::new(Mutex::new(FilteredIndex::default()))
+            })
+            .clone()
         });
-        let mut state = state.lock().unwrap();
+        let mut index = index.lock().unwrap();
 
-        // TODO: cache the globset
-        let glob = {
-            let mut glob = GlobSetBuilder::new();
-            for target in state.target_filter.targets.clone() {
-                glob.add(target);
-            }
-            glob.build().unwrap()
+        // The store stays locked only while events are taken from it, so
+        // producers aren't blocked while they are filtered or rendered.
+        index.update(&self.collector, &state);
+        let (selected_event, evicted, dropped) = {
+            let events = self.collector.read();
+            let selected = state.selected.and_then(|seq| events.get_shared(seq));
+            (selected, events.evicted(), events.dropped())
         };
+        let filter = index.filter();
 
-        let events = self.collector.events();
-        let filtered_events = events
-            .iter()
-            .filter(|event| state.level_filter.get(event.level) && !glob.is_match(&event.target))
-            .collect::<Vec<_>>();
+        let row_height = constants::SEPARATOR_SPACING + TextStyle::Small.resolve(ui.style()).size;
+
+        if let Some(event) = &selected_event {
+            let mut close = false;
+            let position = state.details;
+            let details = |ui: &mut egui::Ui| {
+                EventDetails::default()
+                    .event(event)
+                    .position(&mut state.details)
+                    .on_close(|| close = true)
+                    .show(ui)
+            };
+            let panel_id = state_mem_id.with("details");
+            match position {
+                DetailsPanel::Right => {
+                    egui::SidePanel::right(panel_id)
+                        .resizable(true)
+                        .default_width(300.0)
+                        .show_inside(ui, details);
+                }
+                DetailsPanel::Bottom => {
+                    egui::TopBottomPanel::bottom(panel_id)
+                        .resizable(true)
+                        .default_height(200.0)
+                        .show_inside(ui, details);
+                }
+            }
+            if close {
+                state.selected = None;
+            }
+        }
+
+        let seqs = index.seqs();
+        let current = state.selected.and_then(|seq| seqs.binary_search(&seq).ok());
+        let navigate = Cell::new(None);
+        ui.horizontal(|ui| {
+            SearchBar::default()
+                .state(&mut state.search)
+                .error(filter.search_error.clone())
+                .rows(current, seqs.len())
+                .on_previous(|| {
+                    let last = seqs.len() - 1;
+                    navigate.set(Some(
+                        current.map_or(last, |i| i.checked_sub(1).unwrap_or(last)),
+                    ));
+                })
+                .on_next(|| {
+                    navigate.set(Some(current.map_or(0, |i| (i + 1) % seqs.len())));
+                })
+                .show(ui);
+            ui.separator();
+            QueryInput::default()
+                .state(&mut state.query)
+                .error(filter.query_error.clone())
+                .show(ui);
+        });
+        let scroll_to_row = navigate.get();
+        if let Some(i) = scroll_to_row {
+            state.selected = Some(seqs[i]);
+        }
 
-        let row_height = constants::SEPARATOR_SPACING
-            + ui.style().text_styles.get(&TextStyle::Small).unwrap().size;
+        let mut table = Table::default();
+        let status: Vec<_> = [
+            (evicted > 0).then(|| format!("{} older events discarded", evicted)),
+            (dropped > 0).then(|| format!("{} events dropped", dropped)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !status.is_empty() {
+            table = table.status(status.join(", "));
+        }
 
-        Table::default()
+        // Exports are written on a thread of their own, which leaves the
+        // outcome here for the next frame.
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let export = ui.data_mut(|data| {
+                data.get_temp_mut_or_insert_with(state_mem_id.with("export"), || {
+                    Arc::new(Mutex::new(ExportTask::default()))
+                })
+                .clone()
+            });
+            let running = {
+                let mut task = export.lock().unwrap();
+                if let Some(result) = task.finished.take() {
+                    state.export.result = Some(result);
+                }
+                task.running
+            };
+            let ctx = ui.ctx().clone();
+            let collector = &self.collector;
+            let index = &index;
+            let export_state = &mut state.export;
+            table = table.actions(move |ui| {
+                ExportMenuButton::default()
+                    .state(export_state)
+                    .running(running)
+                    .on_export(|path, filtered| {
+                        let events: Vec<_> = if filtered {
+                            index.events().to_vec()
+                        } else {
+                            let events = collector.read();
+                            events
+                                .iter_shared_since(0)
+                                .map(|(_, event)| event)
+                                .collect()
+                        };
+                        let path = path.to_owned();
+                        let export = export.clone();
+                        let ctx = ctx.clone();
+                        export.lock().unwrap().running = true;
+                        std::thread::spawn(move || {
+                            let result = File::create(path)
+                                .and_then(|file| {
+                                    write_jsonl(
+                                        BufWriter::new(file),
+                                        events.iter().map(AsRef::as_ref),
+                                    )
+                                })
+                                .map(|count| format!("Exported {} events", count))
+                                .map_err(|err| err.to_string());
+                            {
+                                let mut task = export.lock().unwrap();
+                                task.running = false;
+                                task.finished = Some(result);
+                            }
+                            ctx.request_repaint();
+                        });
+                    })
+                    .show(ui);
+            });
+        }
+
+        let columns = state.columns.clone();
+        let mut clear = false;
+        let response = table
             .on_clear(|| {
-                self.collector.clear();
+                clear = true;
+            })
+            .selected(state.selected)
+            .scroll_to_row(scroll_to_row)
+            .on_row_clicked(|seq| {
+                state.selected = if state.selected == Some(*seq) {
+                    None
+                } else {
+                    Some(*seq)
+                };
+            })
+            .toolbar(|ui| {
+                ColumnsMenuButton::default()
+                    .state(&mut state.columns)
+                    .show(ui);
+                CaptureMenuButton::default()
+                    .collector(&self.collector)
+                    .show(ui);
             })
             .header(|ui| {
                 TableHeader::default()
@@ -86,9 +376,34 @@ impl Widget for Logs {
                     .children(|ui| {
                         TargetMenuButton::default()
                             .state(&mut state.target_filter)
+                            .error(filter.targets_error.clone())
                             .show(ui)
                     })
                     .show(ui);
+                if columns.spans {
+                    TableHeader::default()
+                        .common_props(CommonProps::default().min_width(120.0))
+                        .children(|ui| {
+                            ui.label("Spans");
+                        })
+                        .show(ui);
+                }
+                if columns.location {
+                    TableHeader::default()
+                        .common_props(CommonProps::default().min_width(120.0))
+                        .children(|ui| {
+                            ui.label("Location");
+                        })
+                        .show(ui);
+                }
+                if columns.thread {
+                    TableHeader::default()
+                        .common_props(CommonProps::default().min_width(100.0))
+                        .children(|ui| {
+                            ui.label("Thread");
+                        })
+                        .show(ui);
+                }
                 TableHeader::default()
                     .common_props(CommonProps::default().min_width(120.0))
                     .children(|ui| {
@@ -97,7 +412,10 @@ impl Widget for Logs {
                     .show(ui);
             })
             .row_height(row_height)
-            .row(|ui, event: &CollectedEvent| {
+            .row(|ui, seq: &u64| {
+                let Some(event) = index.get(*seq) else {
+                    return;
+                };
                 TableCell::default()
                     .common_props(CommonProps::default().min_width(100.0))
                     .children(|ui| {
@@ -118,17 +436,67 @@ impl Widget for Logs {
                             .on_hover_text(&event.target);
                     })
                     .show(ui);
+                if columns.spans {
+                    TableCell::default()
+                        .common_props(CommonProps::default().min_width(120.0))
+                        .children(|ui| {
+                            let spans = event.span_breadcrumb();
+                            ui.colored_label(Color32::GRAY, spans.truncate_graphemes(18))
+                                .on_hover_text(spans);
+                        })
+                        .show(ui);
+                }
+                if columns.location {
+                    TableCell::default()
+                        .common_props(CommonProps::default().min_width(120.0))
+                        .children(|ui| {
+                            let module_path = event
+                                .module_path
+                                .clone()
+                                .or_else(|| event.file.clone())
+                                .unwrap_or_default();
+                            let label =
+                                ui.colored_label(Color32::GRAY, module_path.truncate_graphemes(18));
+                            if let Some(location) = event.location() {
+                                label.on_hover_text(location);
+                            }
+                        })
+                        .show(ui);
+                }
+                if columns.thread {
+                    TableCell::default()
+                        .common_props(CommonProps::default().min_width(100.0))
+                        .children(|ui| {
+                            ui.colored_label(
+                                Color32::GRAY,
+                                event.thread.to_string().truncate_graphemes(14),
+                            )
+                            .on_hover_text(&event.thread.id);
+                        })
+                        .show(ui);
+                }
                 TableCell::default()
                     .common_props(CommonProps::default().min_width(120.0))
                     .children(|ui| {
-                        let message = event.fields.get("message").unwrap();
-
-                        ui.style_mut().visuals.override_text_color = Some(Color32::WHITE);
-                        ui.add(Label::new(message).wrap(false))
-                            .on_hover_text(message);
+                        let message = search::message(event);
+                        let job = search::highlight(
+                            &message,
+                            filter.search(),
+                            TextStyle::Body.resolve(ui.style()),
+                            Color32::WHITE,
+                        );
+                        ui.add(Label::new(job).wrap(false)).on_hover_text(message);
                     })
                     .show(ui);
             })
-            .show(ui, filtered_events.iter())
+            .show(ui, seqs);
+
+        if clear {
+            self.collector.clear();
+        }
+
+        ui.data_mut(|data| data.insert_persisted(state_mem_id, state.clone()));
+
+        LogsOutput { response, state }
     }
 }
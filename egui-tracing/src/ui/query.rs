@@ -0,0 +1,572 @@
+//! A small filter expression language, e.g.
+//! `level>=warn AND target:hyper::* AND status=500 AND latency_ms>250`.
+//!
+//! Comparisons are `key op value` where `op` is one of `=`, `!=`, `>`, `>=`,
+//! `<`, `<=`, `:` (glob match) or `~` (regex match). Comparisons can be
+//! combined with `AND`, `OR`, `NOT` and parentheses; juxtaposed comparisons
+//! are combined with `AND`. The keys `level`, `target`, `module`, `file`,
+//! `thread` and `span` refer to the event's metadata, every other key to a
+//! field of the event.
+
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+use tracing::Level;
+
+use crate::tracing::{CollectedEvent, FieldValue};
+
+/// Nesting of parentheses and `NOT`s past which a query is rejected, so that
+/// parsing doesn't overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone)]
+pub enum Query {
+    And(Box<Query>, Box<Query>),
+    Or(Box<Query>, Box<Query>),
+    Not(Box<Query>),
+    Compare(Comparison),
+}
+
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    key: Key,
+    op: Op,
+    value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Key {
+    Level,
+    Target,
+    Module,
+    File,
+    Thread,
+    Span,
+    Field(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Glob,
+    Regex,
+}
+
+#[derive(Debug, Clone)]
+struct Value {
+    text: String,
+    number: Option<f64>,
+    boolean: Option<bool>,
+    level: Option<Level>,
+    pattern: Option<Pattern>,
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub message: String,
+    /// Byte offset into the query at which the error was found.
+    pub position: usize,
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl Query {
+    /// Parses a query, returning `None` for an empty query.
+    pub fn parse(input: &str) -> Result<Option<Self>, QueryError> {
+        let mut parser = Parser {
+            input,
+            pos: 0,
+            depth: 0,
+        };
+        parser.skip_whitespace();
+        if parser.at_end() {
+            return Ok(None);
+        }
+        let query = parser.parse_or()?;
+        parser.skip_whitespace();
+        if !parser.at_end() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(Some(query))
+    }
+
+    pub fn matches(&self, event: &CollectedEvent) -> bool {
+        match self {
+            Self::And(lhs, rhs) => lhs.matches(event) && rhs.matches(event),
+            Self::Or(lhs, rhs) => lhs.matches(event) || rhs.matches(event),
+            Self::Not(query) => !query.matches(event),
+            Self::Compare(comparison) => comparison.matches(event),
+        }
+    }
+}
+
+impl Comparison {
+    fn matches(&self, event: &CollectedEvent) -> bool {
+        match &self.key {
+            Key::Level => {
+                let level = self.value.level.unwrap();
+                // `Level` orders more verbose levels as greater, the query
+                // orders more severe levels as greater.
+                self.op.matches_ordering(level.cmp(&event.level))
+            }
+            Key::Target => self.matches_str(&event.target),
+            Key::Module => self.matches_opt_str(event.module_path.as_deref()),
+            Key::File => self.matches_opt_str(event.file.as_deref()),
+            Key::Thread => self.matches_str(&event.thread.to_string()),
+            Key::Span => {
+                let any = event.spans.iter().any(|span| match self.op {
+                    Op::Ne => self.value.text == span.name,
+                    _ => self.matches_str(&span.name),
+                });
+                any != (self.op == Op::Ne)
+            }
+            Key::Field(name) => match event.fields.get(name) {
+                Some(value) => self.matches_field(value),
+                None => self.op == Op::Ne,
+            },
+        }
+    }
+
+    fn matches_opt_str(&self, value: Option<&str>) -> bool {
+        match value {
+            Some(value) => self.matches_str(value),
+            None => self.op == Op::Ne,
+        }
+    }
+
+    fn matches_str(&self, value: &str) -> bool {
+        match &self.value.pattern {
+            Some(Pattern::Glob(glob)) => glob.is_match(value),
+            Some(Pattern::Regex(regex)) => regex.is_match(value),
+            None => self.op.matches_ordering(value.cmp(&self.value.text)),
+        }
+    }
+
+    fn matches_field(&self, value: &FieldValue) -> bool {
+        if self.value.pattern.is_none() {
+            if let (Some(lhs), Some(rhs)) = (value.as_f64(), self.value.number) {
+                return lhs
+                    .partial_cmp(&rhs)
+                    .is_some_and(|ordering| self.op.matches_ordering(ordering));
+            }
+            if let (Some(lhs), Some(rhs)) = (value.as_bool(), self.value.boolean) {
+                return self.op.matches_ordering(lhs.cmp(&rhs));
+            }
+        }
+        self.matches_str(&value.to_string())
+    }
+}
+
+impl Op {
+    fn matches_ordering(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Eq => ordering.is_eq(),
+            Self::Ne => ordering.is_ne(),
+            Self::Gt => ordering.is_gt(),
+            Self::Ge => ordering.is_ge(),
+            Self::Lt => ordering.is_lt(),
+            Self::Le => ordering.is_le(),
+            Self::Glob | Self::Regex => false,
+        }
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn error(&self, message: &str) -> QueryError {
+        QueryError {
+            message: message.to_owned(),
+            position: self.pos,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.pos += len;
+        &rest[..len]
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let rest = self.rest();
+        let Some(word) = rest.get(..keyword.len()) else {
+            return false;
+        };
+        let boundary = rest[keyword.len()..]
+            .chars()
+            .next()
+            .map_or(true, |c| c.is_whitespace() || c == '(');
+        if word.eq_ignore_ascii_case(keyword) && boundary {
+            self.pos += keyword.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Query, QueryError> {
+        let mut lhs = self.parse_and()?;
+        loop {
+            self.skip_whitespace();
+            if !self.eat_keyword("OR") {
+                return Ok(lhs);
+            }
+            let rhs = self.parse_and()?;
+            lhs = Query::Or(Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn parse_and(&mut self) -> Result<Query, QueryError> {
+        let mut lhs = self.parse_unary()?;
+        loop {
+            self.skip_whitespace();
+            let explicit = self.eat_keyword("AND");
+            if !explicit {
+                let start = self.pos;
+                let ends_group = self.at_end() || self.peek() == Some(')');
+                if ends_group || self.eat_keyword("OR") {
+                    self.pos = start;
+                    return Ok(lhs);
+                }
+            }
+            let rhs = self.parse_unary()?;
+            lhs = Query::And(Box::new(lhs), Box::new(rhs));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, QueryError> {
+        self.skip_whitespace();
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("query is nested too deeply"));
+        }
+        self.depth += 1;
+        let query = self.parse_group();
+        self.depth -= 1;
+        query
+    }
+
+    fn parse_group(&mut self) -> Result<Query, QueryError> {
+        if self.eat("(") {
+            let query = self.parse_or()?;
+            self.skip_whitespace();
+            if !self.eat(")") {
+                return Err(self.error("expected `)`"));
+            }
+            return Ok(query);
+        }
+        if self.eat_keyword("NOT") {
+            return Ok(Query::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_comparison().map(Query::Compare)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Comparison, QueryError> {
+        let key_start = self.pos;
+        let key = self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        if key.is_empty() {
+            return Err(self.error("expected a field name"));
+        }
+        let key = match key {
+            "level" => Key::Level,
+            "target" => Key::Target,
+            "module" => Key::Module,
+            "file" => Key::File,
+            "thread" => Key::Thread,
+            "span" => Key::Span,
+            field => Key::Field(field.to_owned()),
+        };
+
+        self.skip_whitespace();
+        let op = [
+            (">=", Op::Ge),
+            ("<=", Op::Le),
+            ("!=", Op::Ne),
+            ("=", Op::Eq),
+            (">", Op::Gt),
+            ("<", Op::Lt),
+            (":", Op::Glob),
+            ("~", Op::Regex),
+        ]
+        .into_iter()
+        .find(|(token, _)| self.eat(token))
+        .map(|(_, op)| op)
+        .ok_or_else(|| self.error("expected an operator"))?;
+
+        self.skip_whitespace();
+        let value_start = self.pos;
+        let text = self.parse_value()?;
+        let value_error = |message: String| QueryError {
+            message,
+            position: value_start,
+        };
+
+        let pattern = match op {
+            Op::Glob => Some(Pattern::Glob(
+                Glob::new(&text)
+                    .map_err(|err| value_error(err.kind().to_string()))?
+                    .compile_matcher(),
+            )),
+            Op::Regex => Some(Pattern::Regex(
+                Regex::new(&text).map_err(|err| value_error(err.to_string()))?,
+            )),
+            _ => None,
+        };
+
+        let level = if key == Key::Level {
+            if pattern.is_some() {
+                return Err(QueryError {
+                    message: "`level` only supports comparisons".to_owned(),
+                    position: key_start,
+                });
+            }
+            Some(
+                text.parse::<Level>()
+                    .map_err(|_| value_error(format!("unknown level `{}`", text)))?,
+            )
+        } else {
+            None
+        };
+
+        Ok(Comparison {
+            key,
+            op,
+            value: Value {
+                number: text.parse().ok(),
+                boolean: text.parse().ok(),
+                level,
+                pattern,
+                text,
+            },
+        })
+    }
+
+    fn parse_value(&mut self) -> Result<String, QueryError> {
+        if !self.eat("\"") {
+            let value = self.take_while(|c| !c.is_whitespace() && c != '(' && c != ')');
+            if value.is_empty() {
+                return Err(self.error("expected a value"));
+            }
+            return Ok(value.to_owned());
+        }
+
+        let mut value = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.pos += i + 1;
+                    return Ok(value);
+                }
+                '\\' => {
+                    if let Some((_, escaped)) = chars.next() {
+                        value.push(escaped);
+                    }
+                }
+                c => value.push(c),
+            }
+        }
+        self.pos = self.input.len();
+        Err(self.error("unterminated string"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use super::*;
+    use crate::tracing::ThreadInfo;
+
+    fn event(level: Level, target: &str, fields: &[(&str, FieldValue)]) -> CollectedEvent {
+        CollectedEvent {
+            target: target.to_owned(),
+            level,
+            fields: fields
+                .iter()
+                .map(|(key, value)| ((*key).to_owned(), value.clone()))
+                .collect(),
+            time: Local::now(),
+            spans: Vec::new(),
+            file: None,
+            line: None,
+            module_path: None,
+            thread: ThreadInfo {
+                name: None,
+                id: String::new(),
+            },
+        }
+    }
+
+    fn matches(query: &str, event: &CollectedEvent) -> bool {
+        Query::parse(query).unwrap().unwrap().matches(event)
+    }
+
+    fn error(query: &str) -> QueryError {
+        Query::parse(query).unwrap_err()
+    }
+
+    #[test]
+    fn empty_query() {
+        assert!(Query::parse("").unwrap().is_none());
+        assert!(Query::parse("  ").unwrap().is_none());
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let query = "a=1 OR b=1 AND c=1";
+        let a = event(Level::INFO, "app", &[("a", FieldValue::U64(1))]);
+        let b = event(Level::INFO, "app", &[("b", FieldValue::U64(1))]);
+        assert!(matches(query, &a));
+        assert!(!matches(query, &b));
+        assert!(!matches("(a=1 OR b=1) AND c=1", &a));
+        assert!(matches("(a=1 OR b=1) and c!=1", &b));
+    }
+
+    #[test]
+    fn not_binds_tighter_than_and() {
+        let b = event(Level::INFO, "app", &[("b", FieldValue::U64(1))]);
+        assert!(matches("NOT a=1 AND b=1", &b));
+        assert!(!matches("NOT (a=1 OR b=1)", &b));
+    }
+
+    #[test]
+    fn juxtaposition_is_and() {
+        let event = event(
+            Level::INFO,
+            "app",
+            &[("a", FieldValue::U64(1)), ("b", FieldValue::U64(2))],
+        );
+        assert!(matches("a=1 b=2", &event));
+        assert!(!matches("a=1 b=1", &event));
+        assert!(matches("a=2 b=1 OR a=1", &event));
+    }
+
+    #[test]
+    fn level_comparisons() {
+        let warn = event(Level::WARN, "app", &[]);
+        assert!(matches("level>=warn", &warn));
+        assert!(matches("level<=warn", &warn));
+        assert!(matches("level=WARN", &warn));
+        assert!(!matches("level>warn", &warn));
+        assert!(matches("level>info", &warn));
+        assert!(!matches("level>=error", &warn));
+        assert!(matches("level<error", &warn));
+        assert!(matches("level>=info", &event(Level::ERROR, "app", &[])));
+        assert!(!matches("level>=info", &event(Level::DEBUG, "app", &[])));
+    }
+
+    #[test]
+    fn field_comparisons() {
+        let event = event(
+            Level::INFO,
+            "hyper::client",
+            &[
+                ("latency_ms", FieldValue::U64(300)),
+                ("ok", FieldValue::Bool(false)),
+                ("path", FieldValue::Str("/api/users".to_owned())),
+            ],
+        );
+        assert!(matches("latency_ms>250", &event));
+        assert!(!matches("latency_ms>=1000", &event));
+        assert!(matches("ok=false", &event));
+        assert!(matches("path:/api/*", &event));
+        assert!(matches("path~^/api/u", &event));
+        assert!(matches("target:hyper::*", &event));
+        assert!(matches("missing!=1", &event));
+        assert!(!matches("missing=1", &event));
+    }
+
+    #[test]
+    fn quoted_values() {
+        let event = event(
+            Level::INFO,
+            "app",
+            &[
+                ("message", FieldValue::Str("hello world".to_owned())),
+                ("quote", FieldValue::Str("say \"hi\" (now)".to_owned())),
+            ],
+        );
+        assert!(matches("message=\"hello world\"", &event));
+        assert!(matches(r#"quote="say \"hi\" (now)""#, &event));
+        assert!(matches("message:\"hello *\" AND NOT message=hello", &event));
+    }
+
+    #[test]
+    fn error_positions() {
+        assert_eq!(error("level>=loud").position, 7);
+        assert_eq!(error("level:warn").position, 0);
+        assert_eq!(error("a=1 )").position, 4);
+        assert_eq!(error("(a=1").position, 4);
+        assert_eq!(error("a").position, 1);
+        assert_eq!(error("a=").position, 2);
+        assert_eq!(error("a=1 AND").position, 7);
+        assert_eq!(error("a=1 OR =2").position, 7);
+        assert_eq!(error("a=\"x").position, 4);
+        assert_eq!(error("a~(").position, 2);
+    }
+
+    #[test]
+    fn nesting_is_capped() {
+        let nested = format!(
+            "{}a=1{}",
+            "(".repeat(MAX_DEPTH - 1),
+            ")".repeat(MAX_DEPTH - 1)
+        );
+        assert!(Query::parse(&nested).is_ok());
+
+        let err = error(&"(".repeat(100_000));
+        assert_eq!(err.message, "query is nested too deeply");
+        assert_eq!(err.position, MAX_DEPTH);
+        assert!(Query::parse(&"NOT ".repeat(100_000)).is_err());
+    }
+}
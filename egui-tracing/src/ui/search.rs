@@ -0,0 +1,65 @@
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, FontId};
+use regex::{Regex, RegexBuilder};
+
+use super::state::Search;
+use crate::tracing::CollectedEvent;
+
+const HIGHLIGHT_COLOR: Color32 = Color32::from_rgb(120, 90, 0);
+
+/// Compiles the search into a regex, escaping plain text searches.
+/// Returns `None` when there is nothing to search for.
+pub fn compile(search: &Search) -> Result<Option<Regex>, regex::Error> {
+    if search.text.is_empty() {
+        return Ok(None);
+    }
+    let pattern = if search.regex {
+        search.text.clone()
+    } else {
+        regex::escape(&search.text)
+    };
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!search.case_sensitive)
+        .build()
+        .map(Some)
+}
+
+pub fn message(event: &CollectedEvent) -> String {
+    event
+        .fields
+        .get("message")
+        .map(ToString::to_string)
+        .unwrap_or_default()
+}
+
+pub fn matches(regex: &Regex, all_fields: bool, event: &CollectedEvent) -> bool {
+    if all_fields {
+        event
+            .fields
+            .values()
+            .any(|value| regex.is_match(&value.to_string()))
+    } else {
+        regex.is_match(&message(event))
+    }
+}
+
+/// Lays out `text` with every match of `regex` highlighted.
+pub fn highlight(text: &str, regex: Option<&Regex>, font_id: FontId, color: Color32) -> LayoutJob {
+    let format = TextFormat::simple(font_id, color);
+    let highlighted = TextFormat {
+        background: HIGHLIGHT_COLOR,
+        ..format.clone()
+    };
+
+    let mut job = LayoutJob::default();
+    let mut end = 0;
+    if let Some(regex) = regex {
+        for found in regex.find_iter(text).filter(|found| !found.is_empty()) {
+            job.append(&text[end..found.start()], 0.0, format.clone());
+            job.append(found.as_str(), 0.0, highlighted.clone());
+            end = found.end();
+        }
+    }
+    job.append(&text[end..], 0.0, format);
+    job
+}
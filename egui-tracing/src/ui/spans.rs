@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Local};
+use egui::{pos2, vec2, Align2, Color32, FontId, Rect, Response, Sense, Ui, Widget};
+
+use super::color::ToColor32;
+use crate::time::DateTimeFormatExt;
+use crate::tracing::collector::EventCollector;
+use crate::tracing::{CollectedSpan, SpansGuard, ThreadInfo};
+
+const LANE_HEIGHT: f32 = 18.0;
+const THREAD_HEADER_HEIGHT: f32 = 20.0;
+const ZOOM_STEP: f32 = 1.5;
+
+/// Timeline of the collected spans, one row of lanes per thread.
+pub struct Spans {
+    collector: EventCollector,
+}
+
+impl Spans {
+    #[must_use]
+    pub const fn new(collector: EventCollector) -> Self {
+        Self { collector }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct SpansState {
+    /// `None` fits the whole timeline into the available width.
+    pixels_per_second: Option<f32>,
+}
+
+/// A span placed in the lanes of its thread.
+#[derive(Debug, Clone, Copy)]
+struct Lane {
+    seq: u64,
+    depth: usize,
+}
+
+/// The lanes of the collected spans, extended with new spans every frame
+/// instead of being rebuilt from all of them.
+#[derive(Debug, Default)]
+struct Layout {
+    cursor: u64,
+    first_seq: u64,
+    threads: BTreeMap<Option<ThreadInfo>, Vec<Lane>>,
+    /// Thread and depth of the placed spans, to place their children.
+    placed: HashMap<u64, (Option<ThreadInfo>, usize)>,
+    /// Spans that weren't entered yet, so their thread is still unknown.
+    unentered: Vec<u64>,
+    start: Option<DateTime<Local>>,
+}
+
+impl Layout {
+    fn update(&mut self, spans: &SpansGuard<'_>) {
+        if self.first_seq != spans.first_seq() {
+            let first_seq = spans.first_seq();
+            self.first_seq = first_seq;
+            for lanes in self.threads.values_mut() {
+                lanes.retain(|lane| lane.seq >= first_seq);
+            }
+            self.threads.retain(|_, lanes| !lanes.is_empty());
+            self.placed.retain(|seq, _| *seq >= first_seq);
+            self.unentered.retain(|seq| *seq >= first_seq);
+        }
+
+        // Spans get their thread when they are first entered.
+        let mut entered = Vec::new();
+        self.unentered.retain(
+            |seq| match spans.get(*seq).and_then(|span| span.thread.clone()) {
+                Some(thread) => {
+                    entered.push((*seq, thread));
+                    false
+                }
+                None => true,
+            },
+        );
+        if !entered.is_empty() {
+            if let Some(lanes) = self.threads.get_mut(&None) {
+                lanes.retain(|lane| !entered.iter().any(|(seq, _)| *seq == lane.seq));
+            }
+            for (seq, thread) in entered {
+                let parent = spans.get(seq).and_then(|span| span.parent);
+                self.place(seq, Some(thread), parent);
+            }
+        }
+
+        for (seq, span) in spans.iter_since(self.cursor) {
+            self.place(seq, span.thread.clone(), span.parent);
+        }
+        self.cursor = spans.next_seq();
+        self.start = spans.iter().next().map(|(_, span)| span.opened);
+    }
+
+    fn place(&mut self, seq: u64, thread: Option<ThreadInfo>, parent: Option<u64>) {
+        let depth = parent
+            .and_then(|parent| self.placed.get(&parent))
+            .filter(|(parent_thread, _)| *parent_thread == thread)
+            .map_or(0, |(_, depth)| depth + 1);
+        if thread.is_none() {
+            self.unentered.push(seq);
+        }
+        self.placed.insert(seq, (thread.clone(), depth));
+        self.threads
+            .entry(thread)
+            .or_default()
+            .push(Lane { seq, depth });
+    }
+}
+
+impl Widget for Spans {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let state_mem_id = ui.id();
+        let mut state = ui
+            .data(|data| data.get_temp::<SpansState>(state_mem_id))
+            .unwrap_or_default();
+
+        let mut clear = false;
+        let response = ui
+            .vertical(|ui| {
+                let layout = ui.data_mut(|data| {
+                    data.get_temp_mut_or_insert_with(state_mem_id.with("layout"), || {
+                        Arc::new(Mutex::new(Layout::default()))
+                    })
+                    .clone()
+                });
+                let mut layout = layout.lock().unwrap();
+                layout.update(&self.collector.read_spans());
+
+                let now = Local::now();
+                let start = layout.start.unwrap_or(now);
+                let total = now
+                    .signed_duration_since(start)
+                    .to_std()
+                    .unwrap_or_default()
+                    .as_secs_f32()
+                    .max(0.001);
+                let fit = ui.available_width() / total;
+                let pixels_per_second = state.pixels_per_second.unwrap_or(fit);
+
+                ui.horizontal(|ui| {
+                    ui.style_mut().visuals.override_text_color = Some(Color32::WHITE);
+                    if ui.button("Zoom In").clicked() {
+                        state.pixels_per_second = Some(pixels_per_second * ZOOM_STEP);
+                    }
+                    if ui.button("Zoom Out").clicked() {
+                        state.pixels_per_second = Some(pixels_per_second / ZOOM_STEP);
+                    }
+                    if ui.button("Fit").on_hover_text("Fit to Width").clicked() {
+                        state.pixels_per_second = None;
+                    }
+                    ui.separator();
+                    if ui.button("Clear").on_hover_text("Clear Spans").clicked() {
+                        clear = true;
+                    }
+                });
+
+                ui.separator();
+
+                let height = layout
+                    .threads
+                    .values()
+                    .map(|lanes| {
+                        let depth = lanes.iter().map(|lane| lane.depth).max().unwrap_or(0);
+                        THREAD_HEADER_HEIGHT + (depth + 1) as f32 * LANE_HEIGHT
+                    })
+                    .sum::<f32>();
+
+                egui::ScrollArea::both()
+                    .auto_shrink([false, false])
+                    .show(ui, |ui| {
+                        let width = (total * pixels_per_second).max(ui.available_width());
+                        let (rect, response) =
+                            ui.allocate_exact_size(vec2(width, height), Sense::hover());
+
+                        if response.hovered() {
+                            let zoom = ui.input(|i| i.zoom_delta());
+                            if zoom != 1.0 {
+                                state.pixels_per_second = Some(pixels_per_second * zoom);
+                            }
+                        }
+
+                        let clip = ui.clip_rect();
+                        let painter = ui.painter_at(rect);
+                        let pointer = response.hover_pos();
+                        let mut hovered = None;
+                        let mut top = rect.top();
+
+                        // Only the visible spans are taken out of the store,
+                        // and painted once it is unlocked again.
+                        let mut headers = Vec::new();
+                        let mut visible = Vec::new();
+                        {
+                            let spans = self.collector.read_spans();
+                            for (thread, lanes) in &layout.threads {
+                                headers.push((top, thread));
+                                top += THREAD_HEADER_HEIGHT;
+
+                                for lane in lanes {
+                                    let Some(span) = spans.get_shared(lane.seq) else {
+                                        continue;
+                                    };
+                                    let offset = span
+                                        .opened
+                                        .signed_duration_since(start)
+                                        .to_std()
+                                        .unwrap_or_default()
+                                        .as_secs_f32();
+                                    let left = rect.left() + offset * pixels_per_second;
+                                    let right = left
+                                        + (span.duration().as_secs_f32() * pixels_per_second)
+                                            .max(1.0);
+                                    let y = top + lane.depth as f32 * LANE_HEIGHT;
+                                    let span_rect = Rect::from_min_max(
+                                        pos2(left, y),
+                                        pos2(right, y + LANE_HEIGHT - 2.0),
+                                    );
+                                    if clip.intersects(span_rect) {
+                                        visible.push((span_rect, span));
+                                    }
+                                }
+
+                                let depth = lanes.iter().map(|lane| lane.depth).max().unwrap_or(0);
+                                top += (depth + 1) as f32 * LANE_HEIGHT;
+                            }
+                        }
+
+                        for (top, thread) in headers {
+                            painter.text(
+                                pos2(clip.left().max(rect.left()) + 4.0, top + 2.0),
+                                Align2::LEFT_TOP,
+                                thread.as_ref().map_or_else(
+                                    || "(not entered)".to_owned(),
+                                    ToString::to_string,
+                                ),
+                                FontId::proportional(12.0),
+                                Color32::GRAY,
+                            );
+                        }
+
+                        for (span_rect, span) in visible {
+                            let mut color = span.level.to_color32();
+                            if span.closed.is_none() {
+                                color = color.gamma_multiply(0.6);
+                            }
+                            painter.rect_filled(span_rect, 2.0, color);
+                            if span_rect.width() > 24.0 {
+                                painter.with_clip_rect(span_rect.intersect(clip)).text(
+                                    span_rect.left_center() + vec2(4.0, 0.0),
+                                    Align2::LEFT_CENTER,
+                                    &span.name,
+                                    FontId::monospace(11.0),
+                                    Color32::WHITE,
+                                );
+                            }
+
+                            if pointer.is_some_and(|pointer| span_rect.contains(pointer)) {
+                                hovered = Some(span);
+                            }
+                        }
+
+                        if let Some(span) = hovered {
+                            response.on_hover_ui_at_pointer(|ui| span_tooltip(ui, &span));
+                        }
+                    });
+            })
+            .response;
+
+        if clear {
+            self.collector.clear_spans();
+        }
+
+        ui.data_mut(|data| data.insert_temp(state_mem_id, state));
+
+        response
+    }
+}
+
+fn span_tooltip(ui: &mut Ui, span: &CollectedSpan) {
+    ui.colored_label(span.level.to_color32(), &span.name);
+    ui.label(&span.target);
+    for (key, value) in &span.fields {
+        ui.label(format!("{}={}", key, value));
+    }
+    ui.separator();
+    ui.label(format!("opened: {}", span.opened.format_detailed()));
+    if let Some(closed) = span.closed {
+        ui.label(format!("closed: {}", closed.format_detailed()));
+    }
+    ui.label(format!("duration: {:.2?}", span.duration()));
+    ui.label(format!("busy: {:.2?}", span.busy));
+    ui.label(format!("idle: {:.2?}", span.idle));
+}
@@ -2,13 +2,82 @@ use globset::Glob;
 use serde::{Deserialize, Serialize};
 use tracing::Level;
 
-#[derive(Debug, Default, Serialize, Deserialize)]
+/// Filters and layout of a [`Logs`](super::Logs) widget.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LogsState {
     pub level_filter: LevelFilter,
     pub target_filter: TargetFilter,
+    pub search: Search,
+    /// Filter expression such as `level>=warn AND target:hyper::* AND status=500`.
+    ///
+    /// Comparisons are `key op value` with `op` one of `=`, `!=`, `>`, `>=`,
+    /// `<`, `<=`, `:` (glob) or `~` (regex), combined with `AND`, `OR`, `NOT`
+    /// and parentheses. The keys `level`, `target`, `module`, `file`, `thread`
+    /// and `span` refer to the event's metadata, other keys to its fields.
+    pub query: String,
+    pub columns: Columns,
+    pub details: DetailsPanel,
+    pub export: ExportState,
+    /// Sequence number of the event shown in the details panel.
+    #[serde(skip)]
+    pub selected: Option<u64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Settings of the JSON Lines export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportState {
+    pub path: String,
+    /// Exports only the events passing the filters.
+    pub filtered: bool,
+    /// Outcome of the last export.
+    #[serde(skip)]
+    pub result: Option<Result<String, String>>,
+}
+
+impl Default for ExportState {
+    fn default() -> Self {
+        Self {
+            path: "logs.jsonl".to_owned(),
+            filtered: false,
+            result: None,
+        }
+    }
+}
+
+/// Where the details panel of the selected event is docked.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DetailsPanel {
+    #[default]
+    Right,
+    Bottom,
+}
+
+/// Text search over the event messages.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Search {
+    pub text: String,
+    /// Treats `text` as a regular expression instead of a literal.
+    pub regex: bool,
+    pub case_sensitive: bool,
+    /// Searches all field values instead of only the message.
+    pub all_fields: bool,
+}
+
+/// Optional columns of the logs table.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Columns {
+    pub spans: bool,
+    pub location: bool,
+    pub thread: bool,
+}
+
+/// Which levels are shown.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LevelFilter {
     pub trace: bool,
     pub debug: bool,
@@ -17,10 +86,75 @@ pub struct LevelFilter {
     pub error: bool,
 }
 
+/// Events are shown if their target matches any include rule (or there are
+/// no include rules) and doesn't match any exclude rule.
 #[derive(Debug, Default, Clone, Serialize, Deserialize, Hash)]
+#[serde(default)]
 pub struct TargetFilter {
+    /// Text of the target menu's input field.
     pub input: String,
-    pub targets: Vec<Glob>,
+    /// Mode of the rule added from the target menu.
+    pub input_mode: TargetMode,
+    pub targets: Vec<TargetRule>,
+}
+
+/// A glob matched against event targets, e.g. `hyper::*`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub struct TargetRule {
+    pub glob: Glob,
+    pub mode: TargetMode,
+}
+
+/// Whether matching events of a [`TargetRule`] are shown or hidden.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Hash)]
+pub enum TargetMode {
+    Include,
+    #[default]
+    Exclude,
+}
+
+impl TargetFilter {
+    pub fn new(targets: Vec<TargetRule>) -> Self {
+        Self {
+            targets,
+            ..Default::default()
+        }
+    }
+}
+
+impl TargetRule {
+    /// Shows only events whose target matches `glob` (and any other
+    /// including rule).
+    pub fn include(glob: &str) -> Result<Self, globset::Error> {
+        Ok(Self {
+            glob: Glob::new(glob)?,
+            mode: TargetMode::Include,
+        })
+    }
+
+    /// Hides events whose target matches `glob`.
+    pub fn exclude(glob: &str) -> Result<Self, globset::Error> {
+        Ok(Self {
+            glob: Glob::new(glob)?,
+            mode: TargetMode::Exclude,
+        })
+    }
+}
+
+impl TargetMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Include => Self::Exclude,
+            Self::Exclude => Self::Include,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Include => "Include",
+            Self::Exclude => "Exclude",
+        }
+    }
 }
 
 impl Default for LevelFilter {
@@ -36,6 +170,17 @@ impl Default for LevelFilter {
 }
 
 impl LevelFilter {
+    /// Shows `level` and all levels more severe than it.
+    pub fn at_least(level: Level) -> Self {
+        Self {
+            trace: Level::TRACE <= level,
+            debug: Level::DEBUG <= level,
+            info: Level::INFO <= level,
+            warn: Level::WARN <= level,
+            error: true,
+        }
+    }
+
     pub fn get(&self, level: Level) -> bool {
         match level {
             Level::TRACE => self.trace,
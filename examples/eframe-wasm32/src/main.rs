@@ -6,12 +6,14 @@ use egui_tracing::tracing_subscriber;
 use egui_tracing::tracing_subscriber::layer::SubscriberExt;
 #[cfg(target_arch = "wasm32")]
 use egui_tracing::tracing_subscriber::util::SubscriberInitExt;
+#[cfg(target_arch = "wasm32")]
+use egui_tracing::tracing_subscriber::Layer;
 
 #[cfg(target_arch = "wasm32")]
 fn main() {
     let collector = egui_tracing::EventCollector::default();
     tracing_subscriber::registry()
-        .with(collector.clone())
+        .with(collector.clone().with_filter(collector.capture_filter()))
         .init();
 
     let web_options = eframe::WebOptions::default();
@@ -20,7 +22,10 @@ fn main() {
             .start(
                 "eframe-canvas",
                 web_options,
-                Box::new(|_cc| Box::new(MyApp::new(collector))),
+                Box::new(|cc| {
+                    collector.set_egui_context(cc.egui_ctx.clone());
+                    Box::new(MyApp::new(collector))
+                }),
             )
             .await
             .expect("failed to start eframe");
@@ -3,11 +3,12 @@ use egui_tracing::tracing::collector::EventCollector;
 use egui_tracing::tracing_subscriber;
 use egui_tracing::tracing_subscriber::layer::SubscriberExt;
 use egui_tracing::tracing_subscriber::util::SubscriberInitExt;
+use egui_tracing::tracing_subscriber::Layer;
 
 fn main() {
     let collector = egui_tracing::EventCollector::default();
     tracing_subscriber::registry()
-        .with(collector.clone())
+        .with(collector.clone().with_filter(collector.capture_filter()))
         .init();
 
     let options = eframe::NativeOptions {
@@ -19,7 +20,10 @@ fn main() {
     eframe::run_native(
         "tracing",
         options,
-        Box::new(|_cc| Box::new(MyApp::new(collector))),
+        Box::new(|cc| {
+            collector.set_egui_context(cc.egui_ctx.clone());
+            Box::new(MyApp::new(collector))
+        }),
     )
     .unwrap();
 }